@@ -1,9 +1,15 @@
+#[derive(Clone)]
 pub enum Response<A, S> {
     Success(A, S),
     Reject,
+    // More input is required, at least the given number of bytes
+    Incomplete(usize),
+    // A failure after a cut i.e. no alternative should be tried
+    Fatal,
 }
 
 impl<A, S> Response<A, S> {
+    // Incomplete and Fatal are folded as a reject: match the response to tell them apart
     pub fn fold<FS, FR, B>(self, success: FS, reject: FR) -> B
     where
         FS: Fn(A, S) -> B,
@@ -11,14 +17,14 @@ impl<A, S> Response<A, S> {
     {
         match self {
             Response::Success(a, s) => success(a, s),
-            Response::Reject => reject(),
+            Response::Reject | Response::Incomplete(_) | Response::Fatal => reject(),
         }
     }
 }
 
 #[cfg(test)]
 mod tests_response {
-    use crate::Response::{Reject, Success};
+    use crate::Response::{Fatal, Incomplete, Reject, Success};
 
     type Response<A> = crate::Response<A, ()>;
 
@@ -35,4 +41,18 @@ mod tests_response {
 
         assert_eq!(v.fold(|_, _| true, || false), false);
     }
+
+    #[test]
+    fn it_fold_an_incomplete() {
+        let v: Response<u32> = Incomplete(1);
+
+        assert_eq!(v.fold(|_, _| true, || false), false);
+    }
+
+    #[test]
+    fn it_fold_a_fatal_failure() {
+        let v: Response<u32> = Fatal;
+
+        assert_eq!(v.fold(|_, _| true, || false), false);
+    }
 }
@@ -4,7 +4,7 @@
 // Stage 1: "The 'Java' addict approach"
 //
 
-use response::Response::{Reject, Success};
+use response::Response::{Fatal, Incomplete, Reject, Success};
 
 //  ------------------------------------------------------------------------------------------------
 
@@ -31,7 +31,7 @@ impl Parse<char> for Satisfy {
             let c = s.chars().next().unwrap();
 
             if f(c) {
-                return Success(c, s[1..].to_string());
+                return Success(c, s[c.len_utf8()..].to_string());
             }
         }
 
@@ -86,6 +86,16 @@ mod tests_satisfy {
         assert_eq!(response.fold(|_, _| false, || true), true);
     }
 
+    #[test]
+    fn it_parse_a_multibyte_character() {
+        let response = any().parse("\u{e9}a".to_string());
+
+        assert_eq!(
+            response.fold(|v, s| v == '\u{e9}' && s == "a", || false),
+            true
+        );
+    }
+
     #[test]
     fn it_parse_another_specific_character() {
         let response = not('b').parse("a".to_string());
@@ -123,8 +133,12 @@ impl<A, B> Parse<(A, B)> for And<A, B> {
                 match right.parse(s1) {
                 Success(v2, s2) => Success((v1, v2), s2),
                 Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
             },
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -185,13 +199,15 @@ impl<A> Parse<Vec<A>> for Repeat<A> {
                     source = s;
                     values.push(a);
                 }
-                _ => {
+                Reject => {
                     if !*opt && values.is_empty() {
                         return Reject;
                     }
 
                     return Success(values, source);
                 }
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
             }
         }
     }
@@ -256,6 +272,13 @@ mod tests_delimited_string {
     fn it_parse_an_empty_string() {
         let response = delimited_string().parse(String::from("\"\""));
 
-        assert_eq!(response.fold(|(_, (v, _)), _| v.len() == 0, || false), true);
+        assert_eq!(response.fold(|(_, (v, _)), _| v.is_empty(), || false), true);
     }
 }
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Extensions
+//
+
+pub mod sync;
@@ -4,7 +4,7 @@
 // Stage 2: "The 'Java' addict approach but without string clone"
 //
 
-use response::Response::{Reject, Success};
+use response::Response::{Fatal, Incomplete, Reject, Success};
 
 //  ------------------------------------------------------------------------------------------------
 
@@ -122,8 +122,12 @@ impl<A, B> Parse<(A, B)> for And<A, B> {
             Success(v1, s1) => match right.parse(s, s1) {
                 Success(v2, s2) => Success((v1, v2), s2),
                 Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
             },
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -184,13 +188,15 @@ impl<A> Parse<Vec<A>> for Repeat<A> {
                     offset = s;
                     values.push(a);
                 }
-                _ => {
+                Reject => {
                     if !*opt && values.is_empty() {
                         return Reject;
                     }
 
                     return Success(values, offset);
                 }
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
             }
         }
     }
@@ -255,6 +261,14 @@ mod tests_delimited_string {
     fn it_parse_an_empty_string() {
         let response = delimited_string().parse(b"\"\"", 0);
 
-        assert_eq!(response.fold(|(_, (v, _)), _| v.len() == 0, || false), true);
+        assert_eq!(response.fold(|(_, (v, _)), _| v.is_empty(), || false), true);
     }
 }
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Extensions
+//
+
+pub mod peg;
+pub mod sync;
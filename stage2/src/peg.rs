@@ -0,0 +1,923 @@
+//
+// Runtime grammars i.e. a PEG text interpreted over boxed parsers
+//
+// Rules are written `name <- expression` and the first rule is the start rule:
+//
+//   list   <- '[' values? ']'
+//   values <- value (',' value)*
+//   value  <- [0-9]+ / list
+//
+// - `a / b` is an ordered choice and `a b` a sequence,
+// - `a*`, `a+` and `a?` repeat or make optional, `&a` and `!a` are lookaheads,
+// - `'text'` or `"text"` are literals, `[a-z_]` is a class, `.` is any UTF-8 character,
+// - `#` starts a comment up to the end of the line.
+//
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::sync::Repeat;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Syntax trees and errors
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tree {
+    Text(String),
+    List(Vec<Tree>),
+    Node(String, Box<Tree>),
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Syntax(usize, usize, &'static str),
+    Undefined(String),
+    Duplicate(String),
+    LeftRecursion(String),
+    NullableRepeat(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Syntax(line, column, expected) => {
+                write!(
+                    f,
+                    "syntax error at {}:{}: expected {}",
+                    line, column, expected
+                )
+            }
+            Error::Undefined(name) => write!(f, "undefined rule `{}`", name),
+            Error::Duplicate(name) => write!(f, "duplicate rule `{}`", name),
+            Error::LeftRecursion(name) => write!(f, "left recursive rule `{}`", name),
+            Error::NullableRepeat(name) => {
+                write!(f, "repetition of a nullable expression in rule `{}`", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The grammar AST
+//
+
+enum Expr {
+    Rule(String),
+    Literal(Vec<u8>),
+    Class(Vec<(char, char)>, bool),
+    Any,
+    Sequence(Vec<Expr>),
+    Choice(Vec<Expr>),
+    Repeat(bool, Box<Expr>),
+    Optional(Box<Expr>),
+    Lookahead(bool, Box<Expr>),
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The grammar reader
+//
+
+struct Reader<'t> {
+    s: &'t [u8],
+    o: usize,
+}
+
+impl<'t> Reader<'t> {
+    fn error<A>(&self, expected: &'static str) -> Result<A, Error> {
+        let before = &self.s[..self.o];
+        let line = before.iter().filter(|c| **c == b'\n').count() + 1;
+        let column = self.o
+            - before
+                .iter()
+                .rposition(|c| *c == b'\n')
+                .map_or(0, |p| p + 1)
+            + 1;
+
+        Err(Error::Syntax(line, column, expected))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.o).copied()
+    }
+
+    fn spacing(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == b'#' {
+                while self.peek().is_some_and(|c| c != b'\n') {
+                    self.o += 1;
+                }
+            } else if c.is_ascii_whitespace() {
+                self.o += 1;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn eat(&mut self, t: &str) -> bool {
+        if self.s[self.o..].starts_with(t.as_bytes()) {
+            self.o += t.len();
+            self.spacing();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        let start = self.o;
+
+        if self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == b'_')
+        {
+            while self
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_')
+            {
+                self.o += 1;
+            }
+
+            let name = String::from_utf8_lossy(&self.s[start..self.o]).into_owned();
+            self.spacing();
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    // An identifier followed by `<-` starts the next definition
+
+    fn definition_ahead(&mut self) -> bool {
+        let start = self.o;
+        let ahead = self.identifier().is_some() && self.eat("<-");
+        self.o = start;
+        ahead
+    }
+
+    fn character(&mut self) -> Result<char, Error> {
+        let c = match self.peek() {
+            Some(b'\\') => {
+                self.o += 1;
+                match self.peek() {
+                    Some(b'n') => '\n',
+                    Some(b'r') => '\r',
+                    Some(b't') => '\t',
+                    Some(_) => return self.decoded(),
+                    None => return self.error("an escaped character"),
+                }
+            }
+            Some(_) => return self.decoded(),
+            None => return self.error("a character"),
+        };
+
+        self.o += 1;
+        Ok(c)
+    }
+
+    fn decoded(&mut self) -> Result<char, Error> {
+        match decode(self.s, self.o) {
+            Some((c, n)) => {
+                self.o += n;
+                Ok(c)
+            }
+            None => self.error("a UTF-8 character"),
+        }
+    }
+
+    fn grammar(&mut self) -> Result<Vec<(String, Expr)>, Error> {
+        let mut rules = Vec::new();
+
+        self.spacing();
+
+        while self.peek().is_some() {
+            let name = match self.identifier() {
+                Some(name) => name,
+                None => return self.error("a rule name"),
+            };
+
+            if !self.eat("<-") {
+                return self.error("`<-`");
+            }
+
+            rules.push((name, self.expression()?));
+        }
+
+        if rules.is_empty() {
+            return self.error("a rule");
+        }
+
+        Ok(rules)
+    }
+
+    fn expression(&mut self) -> Result<Expr, Error> {
+        let mut alternatives = vec![self.sequence()?];
+
+        while self.eat("/") {
+            alternatives.push(self.sequence()?);
+        }
+
+        if alternatives.len() == 1 {
+            Ok(alternatives.remove(0))
+        } else {
+            Ok(Expr::Choice(alternatives))
+        }
+    }
+
+    fn sequence(&mut self) -> Result<Expr, Error> {
+        let mut items = Vec::new();
+
+        while !matches!(self.peek(), None | Some(b'/') | Some(b')')) && !self.definition_ahead() {
+            items.push(self.prefix()?);
+        }
+
+        if items.len() == 1 {
+            Ok(items.remove(0))
+        } else {
+            Ok(Expr::Sequence(items))
+        }
+    }
+
+    fn prefix(&mut self) -> Result<Expr, Error> {
+        if self.eat("&") {
+            Ok(Expr::Lookahead(true, Box::new(self.suffix()?)))
+        } else if self.eat("!") {
+            Ok(Expr::Lookahead(false, Box::new(self.suffix()?)))
+        } else {
+            self.suffix()
+        }
+    }
+
+    fn suffix(&mut self) -> Result<Expr, Error> {
+        let primary = self.primary()?;
+
+        if self.eat("*") {
+            Ok(Expr::Repeat(true, Box::new(primary)))
+        } else if self.eat("+") {
+            Ok(Expr::Repeat(false, Box::new(primary)))
+        } else if self.eat("?") {
+            Ok(Expr::Optional(Box::new(primary)))
+        } else {
+            Ok(primary)
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        match self.peek() {
+            Some(b'(') => {
+                self.eat("(");
+                let expression = self.expression()?;
+
+                if self.eat(")") {
+                    Ok(expression)
+                } else {
+                    self.error("`)`")
+                }
+            }
+            Some(b'.') => {
+                self.eat(".");
+                Ok(Expr::Any)
+            }
+            Some(q) if q == b'\'' || q == b'"' => {
+                self.o += 1;
+                let mut text = Vec::new();
+
+                while self.peek().is_some_and(|c| c != q) {
+                    let c = self.character()?;
+                    text.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+
+                if self.peek().is_none() {
+                    return self.error("the end of the literal");
+                }
+
+                self.o += 1;
+                self.spacing();
+                Ok(Expr::Literal(text))
+            }
+            Some(b'[') => {
+                self.o += 1;
+                let negated = self.peek() == Some(b'^');
+                let mut ranges = Vec::new();
+
+                if negated {
+                    self.o += 1;
+                }
+
+                while self.peek().is_some_and(|c| c != b']') {
+                    let start = self.character()?;
+
+                    if self.peek() == Some(b'-') && self.s.get(self.o + 1) != Some(&b']') {
+                        self.o += 1;
+                        ranges.push((start, self.character()?));
+                    } else {
+                        ranges.push((start, start));
+                    }
+                }
+
+                if self.peek().is_none() {
+                    return self.error("the end of the class");
+                }
+
+                self.o += 1;
+                self.spacing();
+                Ok(Expr::Class(ranges, negated))
+            }
+            _ => match self.identifier() {
+                Some(name) => Ok(Expr::Rule(name)),
+                None => self.error("an expression"),
+            },
+        }
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Grammar analysis i.e. undefined or duplicate rules, left recursion and empty loops
+//
+
+fn resolve(e: &Expr, names: &HashMap<String, usize>) -> Result<(), Error> {
+    match e {
+        Expr::Rule(name) if !names.contains_key(name) => Err(Error::Undefined(name.clone())),
+        Expr::Sequence(items) | Expr::Choice(items) => {
+            items.iter().try_for_each(|e| resolve(e, names))
+        }
+        Expr::Repeat(_, e) | Expr::Optional(e) | Expr::Lookahead(_, e) => resolve(e, names),
+        _ => Ok(()),
+    }
+}
+
+fn nullable(e: &Expr, names: &HashMap<String, usize>, rules: &[bool]) -> bool {
+    match e {
+        Expr::Rule(name) => rules[names[name]],
+        Expr::Literal(text) => text.is_empty(),
+        Expr::Class(_, _) | Expr::Any => false,
+        Expr::Sequence(items) => items.iter().all(|e| nullable(e, names, rules)),
+        Expr::Choice(items) => items.iter().any(|e| nullable(e, names, rules)),
+        Expr::Repeat(optional, e) => *optional || nullable(e, names, rules),
+        Expr::Optional(_) | Expr::Lookahead(_, _) => true,
+    }
+}
+
+// A repetition of a nullable expression never ends
+
+fn nullable_repeat(e: &Expr, names: &HashMap<String, usize>, rules: &[bool]) -> bool {
+    match e {
+        Expr::Repeat(_, e) => nullable(e, names, rules) || nullable_repeat(e, names, rules),
+        Expr::Sequence(items) | Expr::Choice(items) => {
+            items.iter().any(|e| nullable_repeat(e, names, rules))
+        }
+        Expr::Optional(e) | Expr::Lookahead(_, e) => nullable_repeat(e, names, rules),
+        _ => false,
+    }
+}
+
+// Rules which can be called without consuming anything first
+
+fn left_calls(e: &Expr, names: &HashMap<String, usize>, rules: &[bool], calls: &mut Vec<usize>) {
+    match e {
+        Expr::Rule(name) => calls.push(names[name]),
+        Expr::Sequence(items) => {
+            for e in items {
+                left_calls(e, names, rules, calls);
+
+                if !nullable(e, names, rules) {
+                    break;
+                }
+            }
+        }
+        Expr::Choice(items) => items
+            .iter()
+            .for_each(|e| left_calls(e, names, rules, calls)),
+        Expr::Repeat(_, e) | Expr::Optional(e) | Expr::Lookahead(_, e) => {
+            left_calls(e, names, rules, calls)
+        }
+        _ => (),
+    }
+}
+
+fn analyse(rules: &[(String, Expr)], names: &HashMap<String, usize>) -> Result<(), Error> {
+    // Names are mapped to their last definition so an earlier one is a duplicate
+
+    for (i, (name, _)) in rules.iter().enumerate() {
+        if names[name] != i {
+            return Err(Error::Duplicate(name.clone()));
+        }
+    }
+
+    for (_, e) in rules {
+        resolve(e, names)?;
+    }
+
+    let mut nullables = vec![false; rules.len()];
+
+    loop {
+        let next: Vec<bool> = rules
+            .iter()
+            .map(|(_, e)| nullable(e, names, &nullables))
+            .collect();
+
+        if next == nullables {
+            break;
+        }
+
+        nullables = next;
+    }
+
+    for (name, e) in rules {
+        if nullable_repeat(e, names, &nullables) {
+            return Err(Error::NullableRepeat(name.clone()));
+        }
+    }
+
+    let calls: Vec<Vec<usize>> = rules
+        .iter()
+        .map(|(_, e)| {
+            let mut calls = Vec::new();
+            left_calls(e, names, &nullables, &mut calls);
+            calls
+        })
+        .collect();
+
+    // A rule reaching itself through left calls is left recursive
+
+    for start in 0..rules.len() {
+        let mut visited = vec![false; rules.len()];
+        let mut pending = calls[start].clone();
+
+        while let Some(rule) = pending.pop() {
+            if rule == start {
+                return Err(Error::LeftRecursion(rules[start].0.clone()));
+            }
+
+            if !visited[rule] {
+                visited[rule] = true;
+                pending.extend(&calls[rule]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Boxed parsers
+//
+
+type Boxed = Box<dyn Parse<Tree> + Send + Sync>;
+
+struct Rules {
+    names: Vec<String>,
+    bodies: Vec<Boxed>,
+}
+
+struct Call(Weak<Rules>, usize);
+
+impl Parse<Tree> for Call {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let Call(rules, index) = self;
+
+        match rules.upgrade() {
+            Some(rules) => match rules.bodies[*index].parse(s, o) {
+                Success(t, s) => Success(Tree::Node(rules.names[*index].clone(), Box::new(t)), s),
+                Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
+            },
+            None => Reject,
+        }
+    }
+}
+
+// Literals are the UTF-8 bytes of the grammar text
+
+struct Literal(Vec<u8>);
+
+impl Parse<Tree> for Literal {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let Literal(text) = self;
+
+        if s[o.min(s.len())..].starts_with(text) {
+            Success(
+                Tree::Text(String::from_utf8_lossy(text).into_owned()),
+                o + text.len(),
+            )
+        } else {
+            Reject
+        }
+    }
+}
+
+// Classes match a UTF-8 character and not a single byte
+
+struct Character(Box<dyn Fn(char) -> bool + Send + Sync>);
+
+impl Parse<Tree> for Character {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let Character(f) = self;
+
+        match decode(s, o) {
+            Some((c, n)) if f(c) => Success(Tree::Text(c.to_string()), o + n),
+            _ => Reject,
+        }
+    }
+}
+
+fn decode(s: &[u8], o: usize) -> Option<(char, usize)> {
+    let n = match *s.get(o)? {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        _ => 4,
+    };
+    let c = std::str::from_utf8(s.get(o..o + n)?).ok()?.chars().next()?;
+
+    Some((c, n))
+}
+
+struct Sequence(Vec<Boxed>);
+
+impl Parse<Tree> for Sequence {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let Sequence(items) = self;
+
+        let mut values = Vec::with_capacity(items.len());
+        let mut offset = o;
+
+        for p in items {
+            match p.parse(s, offset) {
+                Success(v, s) => {
+                    values.push(v);
+                    offset = s;
+                }
+                Reject => return Reject,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Success(Tree::List(values), offset)
+    }
+}
+
+// A Fatal stops every alternative up to the entry point, as a cut does in stage6
+
+struct Choice(Vec<Boxed>);
+
+impl Parse<Tree> for Choice {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let Choice(alternatives) = self;
+
+        for p in alternatives {
+            match p.parse(s, o) {
+                Success(v, s) => return Success(v, s),
+                Reject => (),
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Reject
+    }
+}
+
+struct List(Repeat<Tree>);
+
+impl Parse<Tree> for List {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let List(p) = self;
+
+        match p.parse(s, o) {
+            Success(v, s) => Success(Tree::List(v), s),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+struct Optional(Boxed);
+
+impl Parse<Tree> for Optional {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let Optional(p) = self;
+
+        match p.parse(s, o) {
+            Success(v, s) => Success(Tree::List(vec![v]), s),
+            Reject => Success(Tree::List(vec![]), o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+struct Lookahead(bool, Boxed);
+
+impl Parse<Tree> for Lookahead {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        let Lookahead(positive, p) = self;
+
+        match (p.parse(s, o), positive) {
+            (Success(_, _), true) | (Reject, false) => Success(Tree::List(vec![]), o),
+            (Success(_, _), false) | (Reject, true) => Reject,
+            (Incomplete(n), _) => Incomplete(n),
+            (Fatal, _) => Fatal,
+        }
+    }
+}
+
+fn compile(e: &Expr, names: &HashMap<String, usize>, rules: &Weak<Rules>) -> Boxed {
+    match e {
+        Expr::Rule(name) => Box::new(Call(rules.clone(), names[name])),
+        Expr::Literal(text) => Box::new(Literal(text.clone())),
+        Expr::Class(ranges, negated) => {
+            let ranges = ranges.clone();
+            let negated = *negated;
+
+            Box::new(Character(Box::new(move |c| {
+                ranges.iter().any(|(l, u)| *l <= c && c <= *u) != negated
+            })))
+        }
+        Expr::Any => Box::new(Character(Box::new(|_| true))),
+        Expr::Sequence(items) => Box::new(Sequence(
+            items.iter().map(|e| compile(e, names, rules)).collect(),
+        )),
+        Expr::Choice(items) => Box::new(Choice(
+            items.iter().map(|e| compile(e, names, rules)).collect(),
+        )),
+        Expr::Repeat(optional, e) => {
+            Box::new(List(Repeat(*optional, compile(e, names, rules).into())))
+        }
+        Expr::Optional(e) => Box::new(Optional(compile(e, names, rules))),
+        Expr::Lookahead(positive, e) => Box::new(Lookahead(*positive, compile(e, names, rules))),
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Grammar parser
+//
+
+pub struct Grammar {
+    rules: Arc<Rules>,
+    start: usize,
+}
+
+impl Grammar {
+    pub fn load(text: &str) -> Result<Grammar, Error> {
+        let rules = Reader {
+            s: text.as_bytes(),
+            o: 0,
+        }
+        .grammar()?;
+
+        let names: HashMap<String, usize> = rules
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (name.clone(), i))
+            .collect();
+
+        analyse(&rules, &names)?;
+
+        let rules = Arc::new_cyclic(|weak| Rules {
+            names: rules.iter().map(|(name, _)| name.clone()).collect(),
+            bodies: rules
+                .iter()
+                .map(|(_, e)| compile(e, &names, weak))
+                .collect(),
+        });
+
+        Ok(Grammar { rules, start: 0 })
+    }
+
+    pub fn open<P>(path: P) -> Result<Grammar, Error>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        Grammar::load(&std::fs::read_to_string(path).map_err(Error::Io)?)
+    }
+
+    pub fn start(self, name: &str) -> Result<Grammar, Error> {
+        match self.rules.names.iter().position(|n| n == name) {
+            Some(start) => Ok(Grammar { start, ..self }),
+            None => Err(Error::Undefined(name.to_string())),
+        }
+    }
+}
+
+impl Parse<Tree> for Grammar {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Tree> {
+        Call(Arc::downgrade(&self.rules), self.start).parse(s, o)
+    }
+}
+
+#[cfg(test)]
+mod tests_grammar {
+    use std::sync::Arc;
+
+    use response::Response::Fatal;
+
+    use crate::peg::{Choice, Error, Grammar, List, Literal, Lookahead, Tree};
+    use crate::sync::Repeat;
+    use crate::Parse;
+    use crate::Response;
+
+    struct Failure;
+
+    impl Parse<Tree> for Failure {
+        fn parse(&self, _: &[u8], _: usize) -> Response<Tree> {
+            Fatal
+        }
+    }
+
+    const LIST: &str = "
+        # Nested lists of numbers
+        list   <- '[' values? ']'
+        values <- value (',' value)*
+        value  <- [0-9]+ / list
+    ";
+
+    fn text(t: &Tree) -> String {
+        match t {
+            Tree::Text(t) => t.clone(),
+            Tree::List(ts) => ts.iter().map(text).collect(),
+            Tree::Node(_, t) => text(t),
+        }
+    }
+
+    #[test]
+    fn it_parse_mutually_recursive_rules() {
+        let response = Grammar::load(LIST).unwrap().parse(b"[1,[22,[]],3]", 0);
+
+        assert_eq!(
+            response.fold(|v, s| text(&v) == "[1,[22,[]],3]" && s == 13, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_produce_rule_nodes() {
+        let response = Grammar::load(LIST).unwrap().parse(b"[]", 0);
+
+        assert_eq!(
+            response.fold(
+                |v, _| v
+                    == Tree::Node(
+                        String::from("list"),
+                        Box::new(Tree::List(vec![
+                            Tree::Text(String::from("[")),
+                            Tree::List(vec![]),
+                            Tree::Text(String::from("]"))
+                        ]))
+                    ),
+                || false
+            ),
+            true
+        );
+    }
+
+    #[test]
+    fn it_parse_from_another_start_rule() {
+        let grammar = Grammar::load(LIST).unwrap().start("value").unwrap();
+        let response = grammar.parse(b"42", 0);
+
+        assert_eq!(
+            response.fold(|v, s| text(&v) == "42" && s == 2, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_cannot_parse_an_unterminated_list() {
+        let response = Grammar::load(LIST).unwrap().parse(b"[1,2", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_parse_lookaheads_and_escapes() {
+        let grammar = Grammar::load(r#"comment <- "/*" (!"*/" .)* "*/" [\n]"#).unwrap();
+        let response = grammar.parse(b"/* a * b */\n", 0);
+
+        assert_eq!(response.fold(|_, s| s == 12, || false), true);
+    }
+
+    #[test]
+    fn it_reject_an_undefined_rule() {
+        let error = Grammar::load("a <- b 'c'").err();
+
+        assert_eq!(
+            matches!(error, Some(Error::Undefined(ref n)) if n == "b"),
+            true
+        );
+    }
+
+    #[test]
+    fn it_reject_an_indirect_left_recursion() {
+        let error = Grammar::load("a <- 'x'? b 'y'\nb <- a / 'z'").err();
+
+        assert_eq!(
+            matches!(error, Some(Error::LeftRecursion(ref n)) if n == "a"),
+            true
+        );
+    }
+
+    #[test]
+    fn it_reject_a_duplicate_rule() {
+        let error = Grammar::load("a <- 'x'\nb <- 'y'\na <- 'z'").err();
+
+        assert_eq!(
+            matches!(error, Some(Error::Duplicate(ref n)) if n == "a"),
+            true
+        );
+    }
+
+    #[test]
+    fn it_reject_a_nullable_repetition() {
+        let error = Grammar::load("a <- 'x' b*\nb <- 'y'?").err();
+
+        assert_eq!(
+            matches!(error, Some(Error::NullableRepeat(ref n)) if n == "a"),
+            true
+        );
+    }
+
+    #[test]
+    fn it_propagate_a_fatal_through_a_choice() {
+        let choice = Choice(vec![Box::new(Failure), Box::new(Literal(b"x".to_vec()))]);
+
+        assert_eq!(matches!(choice.parse(b"x", 0), Fatal), true);
+    }
+
+    #[test]
+    fn it_propagate_a_fatal_through_a_repetition() {
+        let response = List(Repeat(true, Arc::new(Failure))).parse(b"x", 0);
+
+        assert_eq!(matches!(response, Fatal), true);
+    }
+
+    #[test]
+    fn it_propagate_a_fatal_through_a_lookahead() {
+        let response = Lookahead(false, Box::new(Failure)).parse(b"x", 0);
+
+        assert_eq!(matches!(response, Fatal), true);
+    }
+
+    #[test]
+    fn it_parse_a_non_ascii_literal() {
+        let grammar = Grammar::load("a <- 'é'").unwrap();
+        let response = grammar.parse("é".as_bytes(), 0);
+
+        assert_eq!(
+            response.fold(|v, s| text(&v) == "é" && s == 2, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_parse_a_non_ascii_class() {
+        let grammar = Grammar::load("a <- [à-ï]").unwrap();
+        let response = grammar.parse("é".as_bytes(), 0);
+
+        assert_eq!(
+            response.fold(|v, s| text(&v) == "é" && s == 2, || false),
+            true
+        );
+        assert_eq!(
+            grammar.parse("ò".as_bytes(), 0).fold(|_, _| false, || true),
+            true
+        );
+        assert_eq!(grammar.parse(&[0xC3], 0).fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_accept_a_guarded_recursion() {
+        let grammar = Grammar::load("a <- 'x' a / 'y'");
+
+        assert_eq!(grammar.is_ok(), true);
+    }
+
+    #[test]
+    fn it_report_a_syntax_error() {
+        let error = Grammar::load("a <- 'x'\nb <- ('y'").err();
+
+        assert_eq!(
+            error.map(|e| e.to_string()),
+            Some(String::from("syntax error at 2:10: expected `)`"))
+        );
+    }
+}
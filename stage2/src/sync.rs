@@ -0,0 +1,199 @@
+//
+// Thread safe parsers i.e. Send + Sync closures and shared parsers
+//
+// The base parsers keep their unbounded boxes so a closure capturing an Rc is still accepted;
+// these variants are used by grammars built once and shared between threads.
+//
+
+use std::sync::Arc;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::Parse;
+use crate::Response;
+
+pub type SyncParse<A> = Arc<dyn Parse<A> + Send + Sync>;
+
+impl<A, P> Parse<A> for Arc<P>
+where
+    P: Parse<A> + ?Sized,
+{
+    fn parse(&self, s: &[u8], o: usize) -> Response<A> {
+        self.as_ref().parse(s, o) // A shared parser can be boxed again
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+//
+// The Satisfy parser
+//
+
+pub struct Satisfy(pub Box<dyn Fn(char) -> bool + Send + Sync>);
+
+impl Parse<char> for Satisfy {
+    fn parse(&self, s: &[u8], o: usize) -> Response<char> {
+        if o < s.len() {
+            let Satisfy(f) = self;
+
+            let c = s[o] as char; // Simplified approach
+
+            if f(c) {
+                return Success(c, o + 1);
+            }
+        }
+
+        Reject
+    }
+}
+
+pub fn any() -> Satisfy {
+    Satisfy(Box::new(|_| true))
+}
+
+pub fn char(c: char) -> Satisfy {
+    Satisfy(Box::new(move |v| v == c))
+}
+
+pub fn not(c: char) -> Satisfy {
+    Satisfy(Box::new(move |v| v != c))
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The And parser
+//
+
+pub struct And<A, B>(pub SyncParse<A>, pub SyncParse<B>);
+
+pub fn and<A, B>(a: SyncParse<A>, b: SyncParse<B>) -> And<A, B> {
+    And(a, b)
+}
+
+impl<A, B> Parse<(A, B)> for And<A, B> {
+    fn parse(&self, s: &[u8], o: usize) -> Response<(A, B)> {
+        let And(left, right) = self;
+
+        match left.parse(s, o) {
+            Success(v1, s1) => match right.parse(s, s1) {
+                Success(v2, s2) => Success((v1, v2), s2),
+                Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
+            },
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Repeatable parser
+//
+
+pub struct Repeat<A>(pub bool, pub SyncParse<A>);
+
+pub fn rep<A>(p: SyncParse<A>) -> Repeat<A> {
+    Repeat(false, p)
+}
+
+pub fn optrep<A>(p: SyncParse<A>) -> Repeat<A> {
+    Repeat(true, p)
+}
+
+impl<A> Parse<Vec<A>> for Repeat<A> {
+    fn parse(&self, s: &[u8], o: usize) -> Response<Vec<A>> {
+        let Repeat(opt, p) = self;
+
+        let mut values: Vec<A> = Vec::with_capacity(if *opt { 0 } else { 1 });
+        let mut offset = o;
+
+        loop {
+            let result = p.parse(s, offset);
+
+            match result {
+                Success(a, s) => {
+                    offset = s;
+                    values.push(a);
+                }
+                Reject => {
+                    if !*opt && values.is_empty() {
+                        return Reject;
+                    }
+
+                    return Success(values, offset);
+                }
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_sync {
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::sync::{and, char, not, optrep, rep, SyncParse};
+    use crate::Parse;
+    use crate::Satisfy;
+
+    // Compiling is the assertion
+
+    fn send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn it_assert_parsers_are_send_and_sync() {
+        send_sync(&char('a'));
+        send_sync(&and(Arc::new(char('a')), Arc::new(char('b'))));
+        send_sync(&rep(Arc::new(char('a'))));
+    }
+
+    #[test]
+    fn it_keep_unsynchronized_closures_in_the_base_parsers() {
+        let expected = Rc::new('a');
+        let parser = Satisfy(Box::new(move |c| c == *expected));
+
+        assert_eq!(parser.parse(b"a", 0).fold(|v, _| v == 'a', || false), true);
+    }
+
+    type Delimited = (char, (Vec<char>, char));
+
+    #[test]
+    fn it_share_a_parser_between_threads() {
+        let parser: SyncParse<Delimited> = Arc::new(and(
+            Arc::new(char('"')),
+            Arc::new(and(
+                Arc::new(optrep(Arc::new(not('"')))),
+                Arc::new(char('"')),
+            )),
+        ));
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let parser = parser.clone();
+                thread::spawn(move || {
+                    parser
+                        .parse(b"\"aaa\"", 0)
+                        .fold(|(_, (v, _)), _| v.len(), || 0)
+                })
+            })
+            .collect();
+
+        let lengths: Vec<usize> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+
+        assert_eq!(lengths, vec![3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn it_share_a_sub_parser() {
+        let digit: SyncParse<char> = Arc::new(char('1'));
+        let parser = and(digit.clone(), digit);
+        let response = parser.parse(b"11", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('1', '1'), || false), true);
+    }
+}
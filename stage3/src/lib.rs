@@ -6,7 +6,7 @@
 
 use std::marker::PhantomData;
 
-use response::Response::{Reject, Success};
+use response::Response::{Fatal, Incomplete, Reject, Success};
 
 //  ------------------------------------------------------------------------------------------------
 
@@ -136,8 +136,12 @@ where
             Success(v1, s1) => match right.parse(s, s1) {
                 Success(v2, s2) => Success((v1, v2), s2),
                 Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
             },
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -206,7 +210,7 @@ where
                     values.push(a);
                 }
                 _ => {
-                    if !*opt & &values.is_empty() {
+                    if !*opt && values.is_empty() {
                         return Reject;
                     }
 
@@ -280,6 +284,6 @@ mod tests_delimited_string {
     fn it_parse_an_empty_string() {
         let response = delimited_string().parse(b"\"\"", 0);
 
-        assert_eq!(response.fold(|(_, (v, _)), _| v.len() == 0, || false), true);
+        assert_eq!(response.fold(|(_, (v, _)), _| v.is_empty(), || false), true);
     }
 }
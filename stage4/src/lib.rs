@@ -6,7 +6,7 @@
 
 use std::marker::PhantomData;
 
-use response::Response::{Reject, Success};
+use response::Response::{Fatal, Incomplete, Reject, Success};
 
 //  ------------------------------------------------------------------------------------------------
 
@@ -142,8 +142,12 @@ where
             Success(v1, s1) => match right.parse(s, s1) {
                 Success(v2, s2) => Success((v1, v2), s2),
                 Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
             },
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -217,7 +221,7 @@ where
                     values.push(a);
                 }
                 _ => {
-                    if !*opt & &values.is_empty() {
+                    if !*opt && values.is_empty() {
                         return Reject;
                     }
 
@@ -273,6 +277,8 @@ impl<'a> Parse<'a, (&'a [u8], usize, usize)> for Delimited {
         match response {
             Success(_, e) => Success((s, o + 1, e - 1), e),
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -6,7 +6,7 @@
 
 use std::marker::PhantomData;
 
-use response::Response::{Reject, Success};
+use response::Response::{Fatal, Incomplete, Reject, Success};
 
 //  ------------------------------------------------------------------------------------------------
 
@@ -148,8 +148,12 @@ where
             Success(v1, s1) => match right.parse(s, s1) {
                 Success(v2, s2) => Success((v1, v2), s2),
                 Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
             },
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -220,7 +224,7 @@ where
                     values.push(a);
                 }
                 _ => {
-                    if !*opt & &values.is_empty() {
+                    if !*opt && values.is_empty() {
                         return Reject;
                     }
 
@@ -278,6 +282,8 @@ impl<'a> Parse<'a, (&'a [u8], usize, usize)> for Delimited {
         match response {
             Success(_, no) => Success((s, o + 1, no - 1), no),
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -0,0 +1,348 @@
+//
+// #[derive(Parse)] i.e. a stage6 parser generated from an annotated struct or enum
+//
+// - a struct parses its fields in sequence,
+// - an enum tries its variants in order,
+// - #[token("let")] on a struct or a variant recognizes a leading keyword,
+// - #[token("=")] on a field recognizes the token; the field value is Default::default(),
+// - #[many] on a Vec<T> field repeats T and #[sep(",")] repeats T separated by the token.
+//
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, GenericArgument, Ident,
+    LitByteStr, LitStr, PathArguments, Result, Type,
+};
+
+#[proc_macro_derive(Parse, attributes(token, many, sep))]
+pub fn derive_parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Grammar elements
+//
+
+enum Element {
+    Token(LitStr),
+    Field(Type),
+    Many(Type, Option<LitStr>),
+}
+
+fn element(attrs: &[Attribute], ty: &Type) -> Result<Element> {
+    let mut many = false;
+    let mut sep = None;
+
+    for attr in attrs {
+        if attr.path().is_ident("token") {
+            return Ok(Element::Token(attr.parse_args()?));
+        } else if attr.path().is_ident("many") {
+            attr.meta.require_path_only()?;
+            many = true;
+        } else if attr.path().is_ident("sep") {
+            sep = Some(attr.parse_args()?);
+        }
+    }
+
+    if many || sep.is_some() {
+        Ok(Element::Many(vec_element(ty)?, sep))
+    } else {
+        Ok(Element::Field(ty.clone()))
+    }
+}
+
+fn vec_element(ty: &Type) -> Result<Type> {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                if let Some(GenericArgument::Type(ty)) = arguments.args.first() {
+                    if segment.ident == "Vec" {
+                        return Ok(ty.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Err(Error::new(
+        ty.span(),
+        "#[many] and #[sep] expect a Vec<T> field",
+    ))
+}
+
+fn leading(attrs: &[Attribute]) -> Result<Vec<LitStr>> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("token"))
+        .map(|attr| attr.parse_args())
+        .collect()
+}
+
+fn bytes(token: &LitStr) -> LitByteStr {
+    LitByteStr::new(token.value().as_bytes(), token.span())
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Responses of the generated code
+//
+
+// A step of a sequence stops it unless it succeeds
+
+fn attempt(value: TokenStream, response: TokenStream) -> TokenStream {
+    quote! {
+        let (#value, offset) = match #response {
+            ::stage6::Response::Success(v, o) => (v, o),
+            ::stage6::Response::Reject => return ::stage6::Response::Reject,
+            ::stage6::Response::Incomplete(n) => return ::stage6::Response::Incomplete(n),
+            ::stage6::Response::Fatal => return ::stage6::Response::Fatal,
+        };
+    }
+}
+
+// An alternative is left for the next one only when it rejects
+
+fn alternative(response: TokenStream) -> TokenStream {
+    quote! {
+        match #response {
+            ::stage6::Response::Success(v, o) => return ::stage6::Response::Success(v, o),
+            ::stage6::Response::Reject => (),
+            ::stage6::Response::Incomplete(n) => return ::stage6::Response::Incomplete(n),
+            ::stage6::Response::Fatal => return ::stage6::Response::Fatal,
+        }
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Sequences i.e. a struct or a variant
+//
+
+struct Sequence {
+    parse: TokenStream,
+    check: TokenStream,
+}
+
+fn sequence(
+    constructor: TokenStream,
+    keywords: Vec<LitStr>,
+    fields: &Fields,
+    span: Span,
+) -> Result<Sequence> {
+    if keywords.is_empty() && fields.is_empty() {
+        return Err(Error::new(
+            span,
+            "a unit struct or variant expects a #[token]",
+        ));
+    }
+
+    let mut parse = Vec::new();
+    let mut check = Vec::new();
+    let mut values = Vec::new();
+
+    for keyword in keywords {
+        let keyword = bytes(&keyword);
+
+        parse.push(attempt(
+            quote! { _ },
+            quote! { ::stage6::derive::token(#keyword, s, offset) },
+        ));
+        check.push(attempt(
+            quote! { _ },
+            quote! { ::stage6::derive::token(#keyword, s, offset) },
+        ));
+    }
+
+    for (i, field) in fields.iter().enumerate() {
+        let value = format_ident!("v{}", i);
+
+        match element(&field.attrs, &field.ty)? {
+            Element::Token(token) => {
+                let token = bytes(&token);
+
+                let step = attempt(
+                    quote! { _ },
+                    quote! { ::stage6::derive::token(#token, s, offset) },
+                );
+
+                parse.push(quote! {
+                    #step
+                    let #value = ::std::default::Default::default();
+                });
+                check.push(step);
+            }
+            Element::Field(ty) => {
+                parse.push(attempt(
+                    quote! { #value },
+                    quote! { ::stage6::derive::field(s, offset) },
+                ));
+                check.push(attempt(
+                    quote! { _ },
+                    quote! { ::stage6::derive::check_field::<#ty>(s, offset) },
+                ));
+            }
+            Element::Many(ty, sep) => {
+                let sep = match sep {
+                    Some(sep) => {
+                        let sep = bytes(&sep);
+                        quote! { Some(&#sep[..]) }
+                    }
+                    None => quote! { None },
+                };
+
+                parse.push(attempt(
+                    quote! { #value },
+                    quote! { ::stage6::derive::many(s, offset, #sep) },
+                ));
+                check.push(attempt(
+                    quote! { _ },
+                    quote! { ::stage6::derive::check_many::<#ty>(s, offset, #sep) },
+                ));
+            }
+        }
+
+        values.push(value);
+    }
+
+    let value = match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote! { #constructor { #( #names: #values ),* } }
+        }
+        Fields::Unnamed(_) => quote! { #constructor ( #( #values ),* ) },
+        Fields::Unit => quote! { #constructor },
+    };
+
+    Ok(Sequence {
+        parse: quote! {
+            let offset = o;
+            #( #parse )*
+            ::stage6::Response::Success(#value, offset)
+        },
+        check: quote! {
+            let offset = o;
+            #( #check )*
+            ::stage6::Response::Success((), offset)
+        },
+    })
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Expansion
+//
+
+fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "#[derive(Parse)] does not support generic types",
+        ));
+    }
+
+    let name = &input.ident;
+    let sequences = match &input.data {
+        Data::Struct(data) => vec![sequence(
+            quote! { #name },
+            leading(&input.attrs)?,
+            &data.fields,
+            name.span(),
+        )?],
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let variant_name = &variant.ident;
+
+                sequence(
+                    quote! { #name::#variant_name },
+                    leading(&variant.attrs)?,
+                    &variant.fields,
+                    variant.ident.span(),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Data::Union(_) => {
+            return Err(Error::new(
+                name.span(),
+                "#[derive(Parse)] does not support unions",
+            ))
+        }
+    };
+
+    let vis = &input.vis;
+    let parser = format_ident!("{}Parser", name);
+    let parse_fns: Vec<Ident> = (0..sequences.len())
+        .map(|i| format_ident!("parse_{}", i))
+        .collect();
+    let check_fns: Vec<Ident> = (0..sequences.len())
+        .map(|i| format_ident!("check_{}", i))
+        .collect();
+    let parse_bodies = sequences.iter().map(|sequence| &sequence.parse);
+    let check_bodies = sequences.iter().map(|sequence| &sequence.check);
+    let parse_alternatives = parse_fns
+        .iter()
+        .map(|f| alternative(quote! { Self::#f(s, o) }));
+    let check_alternatives = check_fns
+        .iter()
+        .map(|f| alternative(quote! { Self::#f(s, o) }));
+
+    Ok(quote! {
+        #vis struct #parser;
+
+        impl #parser {
+            #(
+                fn #parse_fns(s: &[u8], o: usize) -> ::stage6::Response<#name> {
+                    #parse_bodies
+                }
+            )*
+
+            #(
+                fn #check_fns(s: &[u8], o: usize) -> ::stage6::Response<()> {
+                    #check_bodies
+                }
+            )*
+        }
+
+        impl ::stage6::Combine<#name> for #parser {}
+
+        impl<'a> ::stage6::Parse<'a, #name> for #parser {
+            fn parse(&self, s: &'a [u8], o: usize) -> ::stage6::Response<#name> {
+                #( #parse_alternatives )*
+
+                ::stage6::Response::Reject
+            }
+        }
+
+        impl ::stage6::Check for #parser {
+            fn check(&self, s: &[u8], o: usize) -> ::stage6::Response<()> {
+                #( #check_alternatives )*
+
+                ::stage6::Response::Reject
+            }
+        }
+
+        // Variants may start with any field so the set is the conservative one
+
+        impl ::stage6::choice::First for #parser {
+            fn first(&self) -> ::stage6::choice::FirstSet {
+                ::stage6::choice::FirstSet::all()
+            }
+        }
+
+        impl ::stage6::derive::Grammar for #name {
+            type Parser = #parser;
+
+            fn parser() -> Self::Parser {
+                #parser
+            }
+        }
+    })
+}
@@ -0,0 +1,237 @@
+use std::marker::PhantomData;
+
+use stage6::choice::Or;
+use stage6::cut;
+use stage6::derive::Grammar;
+use stage6::derive::Integer;
+use stage6::Check;
+use stage6::Cut;
+use stage6::Map;
+use stage6::Parse;
+use stage6::Response;
+use stage6_derive::Parse;
+
+#[derive(Parse, Debug, PartialEq)]
+enum Expr {
+    Number(i64),
+    #[token("true")]
+    True,
+    #[token("false")]
+    False,
+    #[token("[")]
+    List(#[sep(",")] Vec<Expr>, #[token("]")] ()),
+    Variable(String),
+}
+
+#[derive(Parse, Debug, PartialEq)]
+#[token("let")]
+struct Let {
+    name: String,
+    #[token("=")]
+    equal: (),
+    value: Box<Expr>,
+    #[token(";")]
+    end: (),
+}
+
+#[derive(Parse, Debug, PartialEq)]
+struct Block {
+    #[many]
+    statements: Vec<Let>,
+    result: Option<Expr>,
+}
+
+#[test]
+fn it_parse_a_struct() {
+    let response = Let::parser().parse(b"let x = 42;", 0);
+
+    assert_eq!(
+        response.fold(
+            |v, s| v.name == "x" && *v.value == Expr::Number(42) && s == 11,
+            || false
+        ),
+        true
+    );
+}
+
+#[test]
+fn it_cannot_parse_a_struct() {
+    let response = Let::parser().parse(b"let x 42;", 0);
+
+    assert_eq!(response.fold(|_, _| false, || true), true);
+}
+
+#[test]
+fn it_parse_enum_variants_in_order() {
+    let response = Expr::parser().parse(b"true", 0);
+
+    assert_eq!(response.fold(|v, _| v == Expr::True, || false), true);
+}
+
+#[test]
+fn it_parse_a_separated_list() {
+    let response = Expr::parser().parse(b"[1, x, [false]]", 0);
+
+    assert_eq!(
+        response.fold(
+            |v, s| v
+                == Expr::List(
+                    vec![
+                        Expr::Number(1),
+                        Expr::Variable(String::from("x")),
+                        Expr::List(vec![Expr::False], ())
+                    ],
+                    ()
+                )
+                && s == 15,
+            || false
+        ),
+        true
+    );
+}
+
+#[test]
+fn it_parse_many_statements() {
+    let response = Block::parser().parse(b"let a = 1; let b = a; b", 0);
+
+    assert_eq!(
+        response.fold(
+            |v, _| v.statements.len() == 2 && v.result == Some(Expr::Variable(String::from("b"))),
+            || false
+        ),
+        true
+    );
+}
+
+#[test]
+fn it_check_many_statements() {
+    let response = Block::parser().check(b"let a = [1,2]; let b = a;", 0);
+
+    assert_eq!(response.fold(|_, s| s == 25, || false), true);
+}
+
+#[derive(Parse, Debug, PartialEq)]
+struct Optionals {
+    #[many]
+    values: Vec<Option<i64>>,
+}
+
+#[test]
+fn it_stop_many_elements_consuming_nothing() {
+    let response = Optionals::parser().parse(b"1 2", 0);
+
+    assert_eq!(
+        response.fold(
+            |v, s| v.values == vec![Some(1), Some(2)] && s == 3,
+            || false
+        ),
+        true
+    );
+    assert_eq!(
+        Optionals::parser()
+            .check(b"1 2", 0)
+            .fold(|_, s| s == 3, || false),
+        true
+    );
+}
+
+#[test]
+fn it_cannot_parse_a_keyword_followed_by_an_identifier() {
+    let response = Let::parser().parse(b"letx = 42;", 0);
+
+    assert_eq!(response.fold(|_, _| false, || true), true);
+}
+
+#[test]
+fn it_parse_a_keyword_followed_by_a_separator() {
+    let response = Let::parser().parse(b"let\tx=42;", 0);
+
+    assert_eq!(response.fold(|v, _| v.name == "x", || false), true);
+}
+
+// A number committed to by a cut once its token is read
+
+#[derive(Debug, PartialEq)]
+struct Strict(i64);
+
+impl Grammar for Strict {
+    type Parser = Map<Cut<Integer, i64>, fn(i64) -> Strict, i64>;
+
+    fn parser() -> Self::Parser {
+        Map(cut(Integer), Strict, PhantomData)
+    }
+}
+
+#[derive(Parse, Debug, PartialEq)]
+enum Committed {
+    #[token("#")]
+    Number(Strict),
+    #[token("#")]
+    Hash(String),
+}
+
+#[derive(Parse, Debug, PartialEq)]
+enum Uncommitted {
+    #[token("#")]
+    Number(i64),
+    #[token("#")]
+    Hash(String),
+}
+
+#[test]
+fn it_parse_after_a_cut_in_a_derived_parser() {
+    let response = Committed::parser().parse(b"#42", 0);
+
+    assert_eq!(
+        response.fold(
+            |v, s| v == Committed::Number(Strict(42)) && s == 3,
+            || false
+        ),
+        true
+    );
+}
+
+#[test]
+fn it_stop_alternatives_after_a_cut_in_a_derived_parser() {
+    let committed = Committed::parser().parse(b"#x", 0);
+    let uncommitted = Uncommitted::parser().parse(b"#x", 0);
+
+    assert_eq!(matches!(committed, Response::Fatal), true);
+    assert_eq!(
+        matches!(Committed::parser().check(b"#x", 0), Response::Fatal),
+        true
+    );
+    assert_eq!(
+        uncommitted.fold(|v, _| v == Uncommitted::Hash(String::from("x")), || false),
+        true
+    );
+}
+
+#[derive(Parse, Debug, PartialEq)]
+struct Tagged {
+    #[token("#")]
+    hash: (),
+    value: Strict,
+}
+
+#[test]
+fn it_propagate_a_cut_out_of_a_derived_sequence() {
+    let response = Tagged::parser().parse(b"#x", 0);
+
+    assert_eq!(matches!(response, Response::Fatal), true);
+}
+
+#[test]
+fn it_stop_an_enclosing_alternative_after_a_cut_in_a_derived_parser() {
+    let parser = Or(
+        Committed::parser(),
+        Map(
+            Uncommitted::parser(),
+            |_| Committed::Hash(String::new()),
+            PhantomData,
+        ),
+        PhantomData,
+    );
+
+    assert_eq!(matches!(parser.parse(b"#x", 0), Response::Fatal), true);
+}
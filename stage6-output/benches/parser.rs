@@ -0,0 +1,260 @@
+#[macro_use]
+extern crate bencher;
+extern crate stage6_output;
+
+use bencher::{black_box, Bencher};
+
+use response::Response::{Reject, Success};
+
+// Both designs parse the same grammars built on the same leaf parser: the only difference is how
+// output types are carried, an associated Output here and the Combine<A> marker in stage6.
+
+type Response<A> = response::Response<A, usize>;
+
+struct Byte<F>(F)
+where
+    F: Fn(u8) -> bool;
+
+impl<F> Byte<F>
+where
+    F: Fn(u8) -> bool,
+{
+    fn next(&self, s: &[u8], o: usize) -> Response<u8> {
+        match s.get(o) {
+            Some(b) if (self.0)(*b) => Success(*b, o + 1),
+            _ => Reject,
+        }
+    }
+}
+
+fn byte(c: u8) -> Byte<impl Fn(u8) -> bool> {
+    Byte(move |b| b == c)
+}
+
+fn not(c: u8) -> Byte<impl Fn(u8) -> bool> {
+    Byte(move |b| b != c)
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The associated Output design
+//
+
+mod output {
+    use stage6_output::{and, optrep, or, rep, Check, Parse};
+
+    use crate::{byte, not, Byte, Response};
+
+    impl<'a, F> Parse<'a> for Byte<F>
+    where
+        F: Fn(u8) -> bool,
+    {
+        type Output = u8;
+
+        fn parse(&self, s: &'a [u8], o: usize) -> Response<u8> {
+            self.next(s, o)
+        }
+    }
+
+    impl<F> Check for Byte<F>
+    where
+        F: Fn(u8) -> bool,
+    {
+        fn check(&self, s: &[u8], o: usize) -> Response<()> {
+            self.next(s, o).fold(|_, o| Response::Success((), o), || Response::Reject)
+        }
+    }
+
+    pub fn delimited_string<'a>() -> impl Parse<'a> {
+        rep!(and!(byte(b'"'), and!(optrep!(not(b'"')), byte(b'"'))))
+    }
+
+    pub fn naive_choice<'a>() -> impl Parse<'a> {
+        rep!(or!(
+            and!(byte(b'{'), byte(b'}')),
+            or!(
+                and!(byte(b'['), byte(b']')),
+                or!(and!(byte(b'('), byte(b')')), and!(byte(b'<'), byte(b'>')))
+            )
+        ))
+    }
+
+    // Each level parses its sub-level twice when the first alternative fails
+
+    pub fn level<'a, 'p, P>(p: &'p P) -> impl Parse<'a, Output = (P::Output, u8)> + 'p
+    where
+        P: Parse<'a>,
+    {
+        or!(and!(p, byte(b'x')), and!(p, byte(b'y')))
+    }
+
+    pub fn parse<'a, E>(parser: E, b: &mut bencher::Bencher, buffer: &'a [u8])
+    where
+        E: Parse<'a>,
+    {
+        b.iter(|| {
+            let buffer = bencher::black_box(buffer);
+
+            match parser.parse(buffer, 0) {
+                Response::Success(_, s) if { s == buffer.len() } => (),
+                _ => panic!("unable parse stream"),
+            }
+        });
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Combine<A> marker design
+//
+
+mod combine {
+    use stage6::{and, optrep, or, rep, Check, Combine, Parse};
+
+    use crate::{byte, not, Byte, Response};
+
+    impl<F> Combine<u8> for Byte<F> where F: Fn(u8) -> bool {}
+
+    impl<'a, F> Parse<'a, u8> for Byte<F>
+    where
+        F: Fn(u8) -> bool,
+    {
+        fn parse(&self, s: &'a [u8], o: usize) -> Response<u8> {
+            self.next(s, o)
+        }
+    }
+
+    impl<F> Check for Byte<F>
+    where
+        F: Fn(u8) -> bool,
+    {
+        fn check(&self, s: &[u8], o: usize) -> Response<()> {
+            self.next(s, o).fold(|_, o| Response::Success((), o), || Response::Reject)
+        }
+    }
+
+    type Delimited = Vec<(u8, (Vec<u8>, u8))>;
+
+    pub fn delimited_string<'a>() -> impl Parse<'a, Delimited> + Combine<Delimited> {
+        rep!(and!(byte(b'"'), and!(optrep!(not(b'"')), byte(b'"'))))
+    }
+
+    pub fn naive_choice<'a>() -> impl Parse<'a, Vec<(u8, u8)>> + Combine<Vec<(u8, u8)>> {
+        rep!(or!(
+            and!(byte(b'{'), byte(b'}')),
+            or!(
+                and!(byte(b'['), byte(b']')),
+                or!(and!(byte(b'('), byte(b')')), and!(byte(b'<'), byte(b'>')))
+            )
+        ))
+    }
+
+    // Each level parses its sub-level twice when the first alternative fails
+
+    pub fn level<'a, 'p, P, A>(p: &'p P) -> impl Parse<'a, (A, u8)> + Combine<(A, u8)> + 'p
+    where
+        P: Parse<'a, A> + Combine<A>,
+        A: 'p,
+    {
+        or!(and!(p, byte(b'x')), and!(p, byte(b'y')))
+    }
+
+    pub fn parse<'a, E, A>(parser: E, b: &mut bencher::Bencher, buffer: &'a [u8])
+    where
+        E: Parse<'a, A> + Combine<A>,
+    {
+        b.iter(|| {
+            let buffer = bencher::black_box(buffer);
+
+            match parser.parse(buffer, 0) {
+                Response::Success(_, s) if { s == buffer.len() } => (),
+                _ => panic!("unable parse stream"),
+            }
+        });
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Benches
+//
+
+fn strings() -> String {
+    format!("\"{}\"", "Hello World!".repeat(100)).repeat(16)
+}
+
+fn output_delimited_string(b: &mut Bencher) {
+    let string = strings();
+    b.bytes = string.len() as u64;
+    output::parse(output::delimited_string(), b, string.as_bytes())
+}
+
+fn combine_delimited_string(b: &mut Bencher) {
+    let string = strings();
+    b.bytes = string.len() as u64;
+    combine::parse(combine::delimited_string(), b, string.as_bytes())
+}
+
+fn output_naive_choice(b: &mut Bencher) {
+    let string = "<>".repeat(1024);
+    b.bytes = string.len() as u64;
+    output::parse(output::naive_choice(), b, string.as_bytes())
+}
+
+fn combine_naive_choice(b: &mut Bencher) {
+    let string = "<>".repeat(1024);
+    b.bytes = string.len() as u64;
+    combine::parse(combine::naive_choice(), b, string.as_bytes())
+}
+
+fn output_naive_backtracking(b: &mut Bencher) {
+    let string = format!("a{}", "y".repeat(12));
+    let data = black_box(string.as_bytes());
+    b.bytes = data.len() as u64;
+    let p0 = byte(b'a');
+    let p1 = output::level(&p0);
+    let p2 = output::level(&p1);
+    let p3 = output::level(&p2);
+    let p4 = output::level(&p3);
+    let p5 = output::level(&p4);
+    let p6 = output::level(&p5);
+    let p7 = output::level(&p6);
+    let p8 = output::level(&p7);
+    let p9 = output::level(&p8);
+    let p10 = output::level(&p9);
+    let p11 = output::level(&p10);
+    let p12 = output::level(&p11);
+    output::parse(p12, b, data)
+}
+
+fn combine_naive_backtracking(b: &mut Bencher) {
+    let string = format!("a{}", "y".repeat(12));
+    let data = black_box(string.as_bytes());
+    b.bytes = data.len() as u64;
+    let p0 = byte(b'a');
+    let p1 = combine::level(&p0);
+    let p2 = combine::level(&p1);
+    let p3 = combine::level(&p2);
+    let p4 = combine::level(&p3);
+    let p5 = combine::level(&p4);
+    let p6 = combine::level(&p5);
+    let p7 = combine::level(&p6);
+    let p8 = combine::level(&p7);
+    let p9 = combine::level(&p8);
+    let p10 = combine::level(&p9);
+    let p11 = combine::level(&p10);
+    let p12 = combine::level(&p11);
+    combine::parse(p12, b, data)
+}
+
+benchmark_group!(
+    benches,
+    output_delimited_string,
+    combine_delimited_string,
+    output_naive_choice,
+    combine_naive_choice,
+    output_naive_backtracking,
+    combine_naive_backtracking
+);
+
+benchmark_main!(benches);
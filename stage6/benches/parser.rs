@@ -2,11 +2,10 @@
 extern crate bencher;
 extern crate stage6;
 
-use std::marker::PhantomData;
-
 use bencher::{black_box, Bencher};
 
 use response::Response::Success;
+use stage6::memo::*;
 use stage6::*;
 
 fn literal_delimited_string(b: &mut Bencher) {
@@ -16,6 +15,113 @@ fn literal_delimited_string(b: &mut Bencher) {
     parse(rep!(delimited_string()), b, data)
 }
 
+// Long strings compare the vectorized scan of Delimited with a character per character scan
+
+fn long_delimited_string(b: &mut Bencher) {
+    let string = format!("\"{}\"", "Hello World!".repeat(100)).repeat(16);
+    let data = string.as_bytes();
+    b.bytes = data.len() as u64;
+    parse(rep!(delimited_string()), b, data)
+}
+
+fn long_character_delimited_string(b: &mut Bencher) {
+    let string = format!("\"{}\"", "Hello World!".repeat(100)).repeat(16);
+    let data = string.as_bytes();
+    b.bytes = data.len() as u64;
+    parse(
+        rep!(and!(char('"'), and!(optrep!(not('"')), char('"')))),
+        b,
+        data,
+    )
+}
+
+fn naive_choice(b: &mut Bencher) {
+    let string = "<>".repeat(1024);
+    let data = string.as_bytes();
+    b.bytes = data.len() as u64;
+    let token = or!(
+        and!(char('{'), char('}')),
+        or!(
+            and!(char('['), char(']')),
+            or!(and!(char('('), char(')')), and!(char('<'), char('>')))
+        )
+    );
+    parse(rep!(token), b, data)
+}
+
+fn dispatch_choice(b: &mut Bencher) {
+    let string = "<>".repeat(1024);
+    let data = string.as_bytes();
+    b.bytes = data.len() as u64;
+    let token = choice!(
+        and!(char('{'), char('}')),
+        and!(char('['), char(']')),
+        and!(char('('), char(')')),
+        and!(char('<'), char('>'))
+    );
+    parse(rep!(token), b, data)
+}
+
+// Each level parses its sub-level twice when the first alternative fails
+
+fn level<'a, 'p, P, A>(p: &'p P) -> impl Parse<'a, (A, char)> + Combine<(A, char)> + 'p
+where
+    P: Parse<'a, A> + Combine<A>,
+    A: 'p,
+{
+    or!(and!(p, char('x')), and!(p, char('y')))
+}
+
+fn naive_backtracking(b: &mut Bencher) {
+    let string = format!("a{}", "y".repeat(12));
+    let data = string.as_bytes();
+    b.bytes = data.len() as u64;
+    let p0 = char('a');
+    let p1 = level(&p0);
+    let p2 = level(&p1);
+    let p3 = level(&p2);
+    let p4 = level(&p3);
+    let p5 = level(&p4);
+    let p6 = level(&p5);
+    let p7 = level(&p6);
+    let p8 = level(&p7);
+    let p9 = level(&p8);
+    let p10 = level(&p9);
+    let p11 = level(&p10);
+    let p12 = level(&p11);
+    parse(p12, b, data)
+}
+
+fn packrat_parsing(b: &mut Bencher) {
+    let string = format!("a{}", "y".repeat(12));
+    let data = string.as_bytes();
+    b.bytes = data.len() as u64;
+    let context = Context::new();
+    let p0 = memo(&context, char('a'));
+    let p1 = memo(&context, level(&p0));
+    let p2 = memo(&context, level(&p1));
+    let p3 = memo(&context, level(&p2));
+    let p4 = memo(&context, level(&p3));
+    let p5 = memo(&context, level(&p4));
+    let p6 = memo(&context, level(&p5));
+    let p7 = memo(&context, level(&p6));
+    let p8 = memo(&context, level(&p7));
+    let p9 = memo(&context, level(&p8));
+    let p10 = memo(&context, level(&p9));
+    let p11 = memo(&context, level(&p10));
+    let p12 = memo(&context, level(&p11));
+    b.iter(|| {
+        let buffer = black_box(data);
+
+        context.reset();
+
+        match p12.parse(buffer, 0) {
+            Success(_, s) if { s == buffer.len() } => (),
+            _ => panic!("unable parse stream"),
+        }
+    });
+}
+
 fn parse<'a, E, A>(parser: E, b: &mut Bencher, buffer: &'a [u8])
 where
     E: Parse<'a, A> + Combine<A>,
@@ -30,6 +136,15 @@ where
     });
 }
 
-benchmark_group!(benches, literal_delimited_string);
+benchmark_group!(
+    benches,
+    literal_delimited_string,
+    long_delimited_string,
+    long_character_delimited_string,
+    naive_choice,
+    dispatch_choice,
+    naive_backtracking,
+    packrat_parsing
+);
 
 benchmark_main!(benches);
@@ -0,0 +1,98 @@
+//
+// Counts the quoted strings of a generated file parsed line by line from a BufRead
+//
+//     cargo run --release -p stage6 --example count_strings -- 4096
+//
+// The argument is the size of the generated file in MiB (64 by default) so 4096 gives a 4 GiB
+// file. Whatever this size the memory used by the parse stays below the reader capacity plus
+// the record limit i.e. 64 KiB + 4 KiB: strings are counted as they are parsed and borrow the
+// record, so no value is collected per byte.
+//
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use response::Response::Success;
+
+use stage6::reader::Reader;
+use stage6::{delimited_string, not, Parse, Response};
+
+const CAPACITY: usize = 64 * 1024;
+const LIMIT: usize = 4 * 1024;
+
+fn generate(path: &Path, size: u64) -> io::Result<u64> {
+    let mut file = BufWriter::with_capacity(CAPACITY, File::create(path)?);
+    let mut written = 0;
+    let mut lines = 0;
+
+    while written < size {
+        let line = format!(
+            "{{\"id\": {}, \"name\": \"item {}\", \"tags\": [\"a\", \"b\"]}}\n",
+            lines, lines
+        );
+        file.write_all(line.as_bytes())?;
+        written += line.len() as u64;
+        lines += 1;
+    }
+
+    file.flush()?;
+
+    Ok(lines)
+}
+
+// The number of strings of a record, other characters being skipped
+
+struct Strings;
+
+impl<'a> Parse<'a, usize> for Strings {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<usize> {
+        let mut strings = 0;
+        let mut offset = o;
+
+        loop {
+            if let Success(_, e) = delimited_string().parse(s, offset) {
+                strings += 1;
+                offset = e;
+            } else if let Success(_, e) = not('"').parse(s, offset) {
+                offset = e;
+            } else {
+                return Success(strings, offset);
+            }
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let size: u64 = env::args()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(64);
+    let path = env::temp_dir().join("stage6-count-strings.txt");
+
+    let lines = generate(&path, size * 1024 * 1024)?;
+
+    let start = Instant::now();
+
+    let file = BufReader::with_capacity(CAPACITY, File::open(&path)?);
+    let mut reader = Reader::lines(file, LIMIT);
+    let mut strings = 0;
+
+    while let Some(count) = reader.next(&Strings) {
+        strings += count?;
+    }
+
+    println!(
+        "{} strings in {} lines ({} MiB) in {:?}",
+        strings,
+        lines,
+        size,
+        start.elapsed()
+    );
+
+    assert_eq!(strings as u64, lines * 6);
+
+    fs::remove_file(path)
+}
@@ -0,0 +1,319 @@
+//
+// Type erased parsers i.e. stage6 parsers stored behind a trait object
+//
+// A boxed, shared or sync parser is bound to the lifetime of its input so a parser whose output
+// borrows the input e.g. `delimited_string()` can be erased. A reusable parser accepts inputs of
+// any lifetime instead: it only erases outputs owning their data but it can be built once and
+// parse buffers read at runtime.
+//
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::choice::{First, FirstSet};
+use crate::rule::Body;
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The BoxedParser parser
+//
+
+pub struct BoxedParser<'a, A>(pub Box<dyn Body<'a, A> + 'a>);
+
+pub fn boxed<'a, P, A>(p: P) -> BoxedParser<'a, A>
+where
+    P: Parse<'a, A> + Check + 'a,
+{
+    BoxedParser(Box::new(p))
+}
+
+impl<A> Combine<A> for BoxedParser<'_, A> {}
+
+impl<'a, A> Parse<'a, A> for BoxedParser<'a, A> {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        self.0.parse(s, o)
+    }
+}
+
+impl<A> Check for BoxedParser<'_, A> {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        self.0.check(s, o)
+    }
+}
+
+impl<A> First for BoxedParser<'_, A> {
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_boxed {
+    use crate::boxed::{boxed, BoxedParser};
+    use crate::char;
+    use crate::delimited_string;
+    use crate::ext::ParserExt;
+    use crate::scan::take_while;
+    use crate::Check;
+    use crate::Parse;
+
+    struct Keywords<'a> {
+        parsers: Vec<BoxedParser<'a, String>>,
+    }
+
+    #[test]
+    fn it_parse_heterogeneous_parsers() {
+        let keywords = Keywords {
+            parsers: vec![
+                boxed(char('a').map(|c| c.to_string())),
+                boxed(
+                    char('b')
+                        .then(char('c'))
+                        .map(|(b, c)| format!("{}{}", b, c)),
+                ),
+            ],
+        };
+        let response = keywords.parsers[1].parse(b"bc", 0);
+
+        assert_eq!(response.fold(|v, s| v == "bc" && s == 2, || false), true);
+    }
+
+    #[test]
+    fn it_combine_boxed_and_static_parsers() {
+        let parser = boxed(char('a')).or(char('b')).then(char('c').boxed());
+        let response = parser.parse(b"bc", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('b', 'c'), || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_borrowed_output() {
+        let input: Vec<u8> = b"\"abc\", x".to_vec();
+        let parsers: Vec<BoxedParser<&[u8]>> = vec![
+            boxed(delimited_string().map(|(s, b, e)| &s[b..e])),
+            boxed(take_while(|b| b != b',')),
+        ];
+
+        let strings: Vec<&[u8]> = parsers
+            .iter()
+            .map(|p| p.parse(&input, 0).fold(|v, _| v, || b""))
+            .collect();
+
+        assert_eq!(strings, vec![&b"abc"[..], &b"\"abc\""[..]]);
+    }
+
+    #[test]
+    fn it_check_a_boxed_parser() {
+        let parser: BoxedParser<Vec<char>> = char('a').many1().boxed();
+        let response = parser.check(b"aab", 0);
+
+        assert_eq!(response.fold(|_, s| s == 2, || false), true);
+    }
+
+    // Without erasure the parser type depends on the chosen branch
+
+    fn sign(negative: bool) -> BoxedParser<'static, char> {
+        if negative {
+            boxed(char('-'))
+        } else {
+            boxed(char('+').or(char('-')))
+        }
+    }
+
+    #[test]
+    fn it_return_different_parsers() {
+        let response = sign(false).parse(b"-", 0);
+
+        assert_eq!(response.fold(|v, _| v == '-', || false), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The SharedParser parser
+//
+
+pub struct SharedParser<'a, A>(pub Rc<dyn Body<'a, A> + 'a>);
+
+pub fn shared<'a, P, A>(p: P) -> SharedParser<'a, A>
+where
+    P: Parse<'a, A> + Check + 'a,
+{
+    SharedParser(Rc::new(p))
+}
+
+impl<A> Clone for SharedParser<'_, A> {
+    fn clone(&self) -> Self {
+        SharedParser(self.0.clone())
+    }
+}
+
+impl<A> Combine<A> for SharedParser<'_, A> {}
+
+impl<'a, A> Parse<'a, A> for SharedParser<'a, A> {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        self.0.parse(s, o)
+    }
+}
+
+impl<A> Check for SharedParser<'_, A> {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        self.0.check(s, o)
+    }
+}
+
+impl<A> First for SharedParser<'_, A> {
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_shared {
+    use crate::boxed::shared;
+    use crate::char;
+    use crate::ext::ParserExt;
+    use crate::Parse;
+
+    #[test]
+    fn it_share_a_parser() {
+        let digit = shared(char('1').or(char('2')));
+        let parser = digit.clone().then(char(',')).then(digit);
+        let response = parser.parse(b"1,2", 0);
+
+        assert_eq!(response.fold(|v, _| v == (('1', ','), '2'), || false), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The SyncParser parser
+//
+
+pub trait SyncBody<'a, A>: Body<'a, A> + Send + Sync {}
+
+impl<'a, A, P> SyncBody<'a, A> for P where P: Body<'a, A> + Send + Sync {}
+
+pub struct SyncParser<'a, A>(pub Arc<dyn SyncBody<'a, A> + 'a>);
+
+pub fn sync<'a, P, A>(p: P) -> SyncParser<'a, A>
+where
+    P: Parse<'a, A> + Check + Send + Sync + 'a,
+{
+    SyncParser(Arc::new(p))
+}
+
+impl<A> Clone for SyncParser<'_, A> {
+    fn clone(&self) -> Self {
+        SyncParser(self.0.clone())
+    }
+}
+
+impl<A> Combine<A> for SyncParser<'_, A> {}
+
+impl<'a, A> Parse<'a, A> for SyncParser<'a, A> {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        self.0.parse(s, o)
+    }
+}
+
+impl<A> Check for SyncParser<'_, A> {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        self.0.check(s, o)
+    }
+}
+
+impl<A> First for SyncParser<'_, A> {
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_sync {
+    use std::thread;
+
+    use crate::boxed::sync;
+    use crate::char;
+    use crate::ext::ParserExt;
+    use crate::Parse;
+
+    #[test]
+    fn it_share_a_parser_between_threads() {
+        let parser = sync(char('a').many1().map(|v| v.len()));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let parser = parser.clone();
+                thread::spawn(move || parser.parse(b"aaa", 0).fold(|v, _| v, || 0))
+            })
+            .collect();
+
+        let lengths: Vec<usize> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+
+        assert_eq!(lengths, vec![3, 3, 3, 3]);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The ReusableParser parser
+//
+
+pub struct ReusableParser<'o, A>(pub Box<dyn for<'i> Body<'i, A> + 'o>);
+
+pub fn reusable<'o, P, A>(p: P) -> ReusableParser<'o, A>
+where
+    P: for<'i> Parse<'i, A> + Check + 'o,
+{
+    ReusableParser(Box::new(p))
+}
+
+impl<A> Combine<A> for ReusableParser<'_, A> {}
+
+impl<'a, A> Parse<'a, A> for ReusableParser<'_, A> {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        self.0.parse(s, o)
+    }
+}
+
+impl<A> Check for ReusableParser<'_, A> {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        self.0.check(s, o)
+    }
+}
+
+impl<A> First for ReusableParser<'_, A> {
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_reusable {
+    use crate::boxed::{reusable, ReusableParser};
+    use crate::char;
+    use crate::ext::ParserExt;
+    use crate::Parse;
+
+    struct Counter {
+        parser: ReusableParser<'static, usize>,
+    }
+
+    #[test]
+    fn it_parse_runtime_inputs() {
+        let counter = Counter {
+            parser: reusable(char('a').many1().map(|v| v.len())),
+        };
+
+        for n in 1..4 {
+            let input: Vec<u8> = vec![b'a'; n];
+            let response = counter.parser.parse(&input, 0);
+
+            assert_eq!(response.fold(|v, s| v == n && s == n, || false), true);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//
+// Chained operators i.e. folding operands as they are parsed: chainl1 needs no intermediate Vec
+// while chainr1 keeps a single stack of pending operands and operators, since the rightmost
+// operand is folded first and recursing instead would overflow the call stack on long chains.
+//
+
+use std::marker::PhantomData;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The ChainL1 parser
+//
+
+pub struct ChainL1<P, O, A, F>(pub P, pub O, pub PhantomData<A>, pub PhantomData<F>)
+where
+    P: Combine<A>,
+    O: Combine<F>;
+
+pub fn chainl1<P, O, A, F>(p: P, op: O) -> ChainL1<P, O, A, F>
+where
+    P: Combine<A>,
+    O: Combine<F>,
+    F: Fn(A, A) -> A,
+{
+    ChainL1(p, op, PhantomData, PhantomData)
+}
+
+impl<P, O, A, F> Combine<A> for ChainL1<P, O, A, F>
+where
+    P: Combine<A>,
+    O: Combine<F>,
+{
+}
+
+impl<'a, P, O, A, F> Parse<'a, A> for ChainL1<P, O, A, F>
+where
+    P: Parse<'a, A> + Combine<A>,
+    O: Parse<'a, F> + Combine<F>,
+    F: Fn(A, A) -> A,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let ChainL1(p, op, _, _) = self;
+
+        let (mut value, mut offset) = match p.parse(s, o) {
+            Success(v, s) => (v, s),
+            Reject => return Reject,
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        };
+
+        loop {
+            match op.parse(s, offset) {
+                Success(f, s1) => match p.parse(s, s1) {
+                    Success(v, s2) => {
+                        value = f(value, v);
+                        offset = s2;
+                    }
+                    Reject => return Success(value, offset),
+                    Incomplete(n) => return Incomplete(n),
+                    Fatal => return Fatal,
+                },
+                Reject => return Success(value, offset),
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+    }
+}
+
+impl<P, O, A, F> Check for ChainL1<P, O, A, F>
+where
+    P: Check + Combine<A>,
+    O: Check + Combine<F>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let ChainL1(p, op, _, _) = self;
+
+        chain_check(p, op, s, o)
+    }
+}
+
+impl<P, O, A, F> First for ChainL1<P, O, A, F>
+where
+    P: First + Combine<A>,
+    O: Combine<F>,
+{
+    fn first(&self) -> FirstSet {
+        let ChainL1(p, _, _, _) = self;
+
+        p.first()
+    }
+}
+
+#[cfg(test)]
+mod tests_fixtures {
+    use crate::char;
+    use crate::map;
+    use crate::Check;
+    use crate::Combine;
+    use crate::Parse;
+
+    type Minus = fn(i64, i64) -> i64;
+
+    pub fn minus() -> impl for<'a> Parse<'a, Minus> + Check + Combine<Minus> {
+        map(char('-'), |_| (|a, b| a - b) as Minus)
+    }
+}
+
+#[cfg(test)]
+mod tests_chainl1 {
+    use crate::chain::chainl1;
+    use crate::chain::tests_fixtures::minus;
+    use crate::tests_fixtures::digit;
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_single_operand() {
+        let response = chainl1(digit(), minus()).parse(b"7", 0);
+
+        assert_eq!(response.fold(|v, s| v == 7 && s == 1, || false), true);
+    }
+
+    #[test]
+    fn it_parse_left_associative_operators() {
+        let response = chainl1(digit(), minus()).parse(b"9-2-3", 0);
+
+        assert_eq!(response.fold(|v, s| v == 4 && s == 5, || false), true);
+    }
+
+    #[test]
+    fn it_do_not_consume_a_dangling_operator() {
+        let response = chainl1(digit(), minus()).parse(b"9-", 0);
+
+        assert_eq!(response.fold(|v, s| v == 9 && s == 1, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_missing_operand() {
+        let response = chainl1(digit(), minus()).parse(b"-1", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_check_left_associative_operators() {
+        let response = chainl1(digit(), minus()).check(b"9-2-3", 0);
+
+        assert_eq!(response.fold(|_, s| s == 5, || false), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The ChainR1 parser
+//
+
+pub struct ChainR1<P, O, A, F>(pub P, pub O, pub PhantomData<A>, pub PhantomData<F>)
+where
+    P: Combine<A>,
+    O: Combine<F>;
+
+pub fn chainr1<P, O, A, F>(p: P, op: O) -> ChainR1<P, O, A, F>
+where
+    P: Combine<A>,
+    O: Combine<F>,
+    F: Fn(A, A) -> A,
+{
+    ChainR1(p, op, PhantomData, PhantomData)
+}
+
+impl<P, O, A, F> Combine<A> for ChainR1<P, O, A, F>
+where
+    P: Combine<A>,
+    O: Combine<F>,
+{
+}
+
+impl<'a, P, O, A, F> Parse<'a, A> for ChainR1<P, O, A, F>
+where
+    P: Parse<'a, A> + Combine<A>,
+    O: Parse<'a, F> + Combine<F>,
+    F: Fn(A, A) -> A,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let ChainR1(p, op, _, _) = self;
+
+        let (mut value, mut offset) = match p.parse(s, o) {
+            Success(v, s) => (v, s),
+            Reject => return Reject,
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        };
+        // Each operand waits with its operator until the operands on its right are folded
+        let mut pending = Vec::new();
+
+        loop {
+            match op.parse(s, offset) {
+                Success(f, s1) => match p.parse(s, s1) {
+                    Success(v, s2) => {
+                        pending.push((value, f));
+                        value = v;
+                        offset = s2;
+                    }
+                    Reject => break,
+                    Incomplete(n) => return Incomplete(n),
+                    Fatal => return Fatal,
+                },
+                Reject => break,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        while let Some((left, f)) = pending.pop() {
+            value = f(left, value);
+        }
+
+        Success(value, offset)
+    }
+}
+
+impl<P, O, A, F> Check for ChainR1<P, O, A, F>
+where
+    P: Check + Combine<A>,
+    O: Check + Combine<F>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let ChainR1(p, op, _, _) = self;
+
+        chain_check(p, op, s, o)
+    }
+}
+
+impl<P, O, A, F> First for ChainR1<P, O, A, F>
+where
+    P: First + Combine<A>,
+    O: Combine<F>,
+{
+    fn first(&self) -> FirstSet {
+        let ChainR1(p, _, _, _) = self;
+
+        p.first()
+    }
+}
+
+// Both chains recognize the same language: the associativity only changes the value.
+
+fn chain_check<P, O>(p: &P, op: &O, s: &[u8], o: usize) -> Response<()>
+where
+    P: Check,
+    O: Check,
+{
+    let mut offset = match p.check(s, o) {
+        Success(_, s) => s,
+        Reject => return Reject,
+        Incomplete(n) => return Incomplete(n),
+        Fatal => return Fatal,
+    };
+
+    loop {
+        match op.check(s, offset) {
+            Success(_, s1) => match p.check(s, s1) {
+                Success(_, s2) => offset = s2,
+                Reject => return Success((), offset),
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            },
+            Reject => return Success((), offset),
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_chainr1 {
+    use crate::chain::chainr1;
+    use crate::chain::tests_fixtures::minus;
+    use crate::tests_fixtures::digit;
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_single_operand() {
+        let response = chainr1(digit(), minus()).parse(b"7", 0);
+
+        assert_eq!(response.fold(|v, s| v == 7 && s == 1, || false), true);
+    }
+
+    #[test]
+    fn it_parse_right_associative_operators() {
+        let response = chainr1(digit(), minus()).parse(b"9-2-3", 0);
+
+        assert_eq!(response.fold(|v, s| v == 10 && s == 5, || false), true);
+    }
+
+    #[test]
+    fn it_do_not_consume_a_dangling_operator() {
+        let response = chainr1(digit(), minus()).parse(b"9-2-", 0);
+
+        assert_eq!(response.fold(|v, s| v == 7 && s == 3, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_missing_operand() {
+        let response = chainr1(digit(), minus()).parse(b"-1", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_parse_a_long_chain() {
+        let input = vec!["1"; 200_000].join("-").into_bytes();
+        let response = chainr1(digit(), minus()).parse(&input, 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == 0 && s == input.len(), || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_check_right_associative_operators() {
+        let response = chainr1(digit(), minus()).check(b"9-2-3", 0);
+
+        assert_eq!(response.fold(|_, s| s == 5, || false), true);
+    }
+}
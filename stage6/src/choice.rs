@@ -0,0 +1,660 @@
+//
+// Ordered choice: naive backtracking vs. FIRST-set driven dispatch
+//
+
+use std::marker::PhantomData;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::report;
+use crate::stream;
+use crate::And;
+use crate::Check;
+use crate::Combine;
+use crate::Cut;
+use crate::Delimited;
+use crate::Frame;
+use crate::Label;
+use crate::Map;
+use crate::Opt;
+use crate::Parse;
+use crate::Repeat;
+use crate::Response;
+use crate::Satisfy;
+use crate::Skip;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The FIRST set i.e. bytes a parser can start with
+//
+
+pub struct FirstSet {
+    pub bytes: [bool; 256],
+    pub nullable: bool,
+}
+
+impl FirstSet {
+    pub fn empty() -> Self {
+        FirstSet {
+            bytes: [false; 256],
+            nullable: false,
+        }
+    }
+
+    // The conservative set of a parser whose first bytes are unknown
+
+    pub fn all() -> Self {
+        FirstSet {
+            bytes: [true; 256],
+            nullable: true,
+        }
+    }
+
+    pub fn byte(b: u8) -> Self {
+        let mut set = Self::empty();
+        set.bytes[b as usize] = true;
+        set
+    }
+
+    pub fn union(mut self, other: FirstSet) -> Self {
+        for (b, o) in self.bytes.iter_mut().zip(other.bytes.iter()) {
+            *b |= *o;
+        }
+        self.nullable |= other.nullable;
+        self
+    }
+
+    pub fn accept(&self, b: u8) -> bool {
+        self.nullable || self.bytes[b as usize]
+    }
+}
+
+pub trait First {
+    fn first(&self) -> FirstSet;
+}
+
+impl<P> First for &P
+where
+    P: First,
+{
+    fn first(&self) -> FirstSet {
+        (*self).first()
+    }
+}
+
+impl<E> First for Satisfy<E>
+where
+    E: Fn(char) -> bool,
+{
+    fn first(&self) -> FirstSet {
+        let Satisfy(f) = self;
+
+        let mut set = FirstSet::empty();
+        for b in 0..=255u8 {
+            set.bytes[b as usize] = f(b as char); // Same simplified approach
+        }
+        set
+    }
+}
+
+impl<L, R, A, B> First for And<L, R, A, B>
+where
+    L: First + Combine<A>,
+    R: First + Combine<B>,
+{
+    fn first(&self) -> FirstSet {
+        let And(left, right, _, _) = self;
+
+        sequence(left.first(), || right.first())
+    }
+}
+
+// The right set only matters when the left one is nullable
+
+fn sequence<F>(left: FirstSet, right: F) -> FirstSet
+where
+    F: FnOnce() -> FirstSet,
+{
+    if left.nullable {
+        let right = right();
+        let nullable = right.nullable;
+
+        let mut set = left.union(right);
+        set.nullable = nullable;
+        set
+    } else {
+        left
+    }
+}
+
+impl<P, A> First for Repeat<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Repeat(opt, p, _) = self;
+
+        let mut set = p.first();
+        set.nullable |= *opt;
+        set
+    }
+}
+
+impl<P, F, A> First for Map<P, F, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Map(p, _, _) = self;
+
+        p.first()
+    }
+}
+
+impl<P, A> First for Opt<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Opt(p, _) = self;
+
+        let mut set = p.first();
+        set.nullable = true;
+        set
+    }
+}
+
+impl<L, R, A, B> First for Skip<L, R, A, B>
+where
+    L: First + Combine<A>,
+    R: First + Combine<B>,
+{
+    fn first(&self) -> FirstSet {
+        let Skip(left, right, _, _) = self;
+
+        sequence(left.first(), || right.first())
+    }
+}
+
+impl<P, A> First for Label<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Label(p, _, _) = self;
+
+        p.first()
+    }
+}
+
+impl<P, A> First for Frame<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Frame(p, _, _) = self;
+
+        p.first()
+    }
+}
+
+impl<P, A> First for Cut<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Cut(p, _) = self;
+
+        p.first()
+    }
+}
+
+impl First for Delimited {
+    fn first(&self) -> FirstSet {
+        FirstSet::byte(b'"')
+    }
+}
+
+#[cfg(test)]
+mod tests_first {
+    use std::marker::PhantomData;
+
+    use crate::char;
+    use crate::choice::First;
+    use crate::cut;
+    use crate::delimited_string;
+    use crate::label;
+    use crate::map;
+    use crate::not;
+    use crate::Opt;
+
+    #[test]
+    fn it_compute_a_character_first_set() {
+        let set = char('a').first();
+
+        assert_eq!(set.bytes[b'a' as usize] && !set.bytes[b'b' as usize], true);
+    }
+
+    #[test]
+    fn it_compute_another_character_first_set() {
+        let set = not('a').first();
+
+        assert_eq!(!set.bytes[b'a' as usize] && set.bytes[b'b' as usize], true);
+    }
+
+    #[test]
+    fn it_compute_a_sequence_first_set() {
+        let set = and!(optrep!(char('a')), char('b')).first();
+
+        assert_eq!(set.accept(b'a') && set.accept(b'b') && !set.nullable, true);
+    }
+
+    #[test]
+    fn it_compute_a_mapped_optional_first_set() {
+        let set = map(Opt(char('a'), PhantomData), |v| v.is_some()).first();
+
+        assert_eq!(set.accept(b'a') && set.nullable, true);
+    }
+
+    #[test]
+    fn it_compute_a_labelled_cut_first_set() {
+        let set = label("a", cut(char('a'))).first();
+
+        assert_eq!(set.accept(b'a') && !set.accept(b'b'), true);
+    }
+
+    #[test]
+    fn it_compute_a_delimited_string_first_set() {
+        let set = delimited_string().first();
+
+        assert_eq!(set.accept(b'"') && !set.accept(b'a'), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Or parser i.e. naive backtracking
+//
+
+pub struct Or<L, R, A>(pub L, pub R, pub PhantomData<A>)
+where
+    L: Combine<A>,
+    R: Combine<A>;
+
+#[macro_export]
+macro_rules! or {
+    ( $ a: expr, $ b: expr) => {
+        $crate::choice::Or($a, $b, ::std::marker::PhantomData)
+    };
+}
+
+impl<L, R, A> Combine<A> for Or<L, R, A>
+where
+    L: Combine<A>,
+    R: Combine<A>,
+{
+}
+
+impl<'a, L, R, A> Parse<'a, A> for Or<L, R, A>
+where
+    L: Parse<'a, A> + Combine<A>,
+    R: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let Or(left, right, _) = self;
+
+        match left.parse(s, o) {
+            Success(v, s) => Success(v, s),
+            Reject => right.parse(s, o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<L, R, A> Check for Or<L, R, A>
+where
+    L: Check + Combine<A>,
+    R: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Or(left, right, _) = self;
+
+        match left.check(s, o) {
+            Success(_, s) => Success((), s),
+            Reject => right.check(s, o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<L, R, A> First for Or<L, R, A>
+where
+    L: First + Combine<A>,
+    R: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Or(left, right, _) = self;
+
+        left.first().union(right.first())
+    }
+}
+
+#[cfg(test)]
+mod tests_or {
+    use crate::char;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_the_first_alternative() {
+        let response = or!(char('a'), char('b')).parse(b"a", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'a', || false), true);
+    }
+
+    #[test]
+    fn it_parse_the_second_alternative() {
+        let response = or!(char('a'), char('b')).parse(b"b", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'b', || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_any_alternative() {
+        let response = or!(char('a'), char('b')).parse(b"c", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Choice parser i.e. dispatch on the next byte using FIRST sets
+//
+
+pub struct Alt<P, R>(pub P, pub R);
+
+pub struct Nil;
+
+pub trait Alternatives<A> {
+    fn firsts(&self, sets: &mut Vec<FirstSet>);
+}
+
+pub trait ParseAt<'a, A> {
+    fn parse_at(&self, i: usize, s: &'a [u8], o: usize) -> Response<A>;
+}
+
+pub trait CheckAt {
+    fn check_at(&self, i: usize, s: &[u8], o: usize) -> Response<()>;
+}
+
+impl<A> Alternatives<A> for Nil {
+    fn firsts(&self, _: &mut Vec<FirstSet>) {}
+}
+
+impl<'a, A> ParseAt<'a, A> for Nil {
+    fn parse_at(&self, _: usize, _: &'a [u8], _: usize) -> Response<A> {
+        Reject
+    }
+}
+
+impl CheckAt for Nil {
+    fn check_at(&self, _: usize, _: &[u8], _: usize) -> Response<()> {
+        Reject
+    }
+}
+
+impl<P, R, A> Alternatives<A> for Alt<P, R>
+where
+    P: First + Combine<A>,
+    R: Alternatives<A>,
+{
+    fn firsts(&self, sets: &mut Vec<FirstSet>) {
+        let Alt(p, r) = self;
+
+        sets.push(p.first());
+        r.firsts(sets)
+    }
+}
+
+impl<'a, P, R, A> ParseAt<'a, A> for Alt<P, R>
+where
+    P: Parse<'a, A> + Combine<A>,
+    R: ParseAt<'a, A>,
+{
+    fn parse_at(&self, i: usize, s: &'a [u8], o: usize) -> Response<A> {
+        let Alt(p, r) = self;
+
+        if i == 0 {
+            p.parse(s, o)
+        } else {
+            r.parse_at(i - 1, s, o)
+        }
+    }
+}
+
+impl<P, R> CheckAt for Alt<P, R>
+where
+    P: Check,
+    R: CheckAt,
+{
+    fn check_at(&self, i: usize, s: &[u8], o: usize) -> Response<()> {
+        let Alt(p, r) = self;
+
+        if i == 0 {
+            p.check(s, o)
+        } else {
+            r.check_at(i - 1, s, o)
+        }
+    }
+}
+
+// Each entry is the bit set of the alternatives which can start with the given byte.
+
+pub struct Choice<B, A>
+where
+    B: Alternatives<A>,
+{
+    branches: B,
+    table: [u64; 256],
+    end: u64,
+    _a: PhantomData<A>,
+}
+
+#[macro_export]
+macro_rules! choice {
+    ( @alt $ a: expr ) => {
+        $crate::choice::Alt($a, $crate::choice::Nil)
+    };
+    ( @alt $ a: expr, $ ( $ b: expr ),+ ) => {
+        $crate::choice::Alt($a, $crate::choice!(@alt $ ( $ b ),+))
+    };
+    ( $ ( $ a: expr ),+ ) => {
+        $crate::choice::Choice::new($crate::choice!(@alt $ ( $ a ),+))
+    };
+}
+
+impl<B, A> Choice<B, A>
+where
+    B: Alternatives<A>,
+{
+    pub fn new(branches: B) -> Self {
+        let mut sets = Vec::new();
+        branches.firsts(&mut sets);
+
+        assert!(sets.len() <= 64, "a choice is limited to 64 alternatives");
+
+        let mut table = [0u64; 256];
+        let mut end = 0u64;
+
+        for (i, set) in sets.iter().enumerate() {
+            for (b, entry) in table.iter_mut().enumerate() {
+                if set.accept(b as u8) {
+                    *entry |= 1 << i;
+                }
+            }
+            if set.nullable {
+                end |= 1 << i;
+            }
+        }
+
+        Choice {
+            branches,
+            table,
+            end,
+            _a: PhantomData,
+        }
+    }
+
+    fn candidates(&self, s: &[u8], o: usize) -> u64 {
+        if o < s.len() {
+            self.table[s[o] as usize]
+        } else {
+            self.end
+        }
+    }
+}
+
+impl<B, A> Combine<A> for Choice<B, A> where B: Alternatives<A> {}
+
+impl<'a, B, A> Parse<'a, A> for Choice<B, A>
+where
+    B: Alternatives<A> + ParseAt<'a, A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        // The first byte is unknown yet: no branch can be selected
+        if o >= s.len() && stream::is_partial() {
+            return Incomplete(1);
+        }
+
+        let mut candidates = self.candidates(s, o);
+
+        // No branch can start here: every first byte was expected
+        if candidates == 0 {
+            return report::reject_with(o, || report::bytes(&self.first()));
+        }
+
+        while candidates != 0 {
+            let i = candidates.trailing_zeros() as usize;
+
+            match self.branches.parse_at(i, s, o) {
+                Success(v, s) => return Success(v, s),
+                Reject => candidates &= candidates - 1,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Reject
+    }
+}
+
+impl<B, A> Check for Choice<B, A>
+where
+    B: Alternatives<A> + CheckAt,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        // The first byte is unknown yet: no branch can be selected
+        if o >= s.len() && stream::is_partial() {
+            return Incomplete(1);
+        }
+
+        let mut candidates = self.candidates(s, o);
+
+        // No branch can start here: every first byte was expected
+        if candidates == 0 {
+            return report::reject_with(o, || report::bytes(&self.first()));
+        }
+
+        while candidates != 0 {
+            let i = candidates.trailing_zeros() as usize;
+
+            match self.branches.check_at(i, s, o) {
+                Success(_, s) => return Success((), s),
+                Reject => candidates &= candidates - 1,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Reject
+    }
+}
+
+impl<B, A> First for Choice<B, A>
+where
+    B: Alternatives<A>,
+{
+    fn first(&self) -> FirstSet {
+        let mut set = FirstSet::empty();
+
+        for (b, entry) in self.table.iter().enumerate() {
+            set.bytes[b] = *entry != 0;
+        }
+        set.nullable = self.end != 0;
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests_choice {
+    use crate::char;
+    use crate::cut;
+    use crate::ext::ParserExt;
+    use crate::label;
+    use crate::map;
+    use crate::span::spanned;
+    use crate::string::escaped_string;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_the_first_alternative() {
+        let response = choice!(char('a'), char('b'), char('c')).parse(b"a", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'a', || false), true);
+    }
+
+    #[test]
+    fn it_parse_the_last_alternative() {
+        let response = choice!(char('a'), char('b'), char('c')).parse(b"c", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'c', || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_any_alternative() {
+        let response = choice!(char('a'), char('b'), char('c')).parse(b"d", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_backtrack_when_first_sets_overlap() {
+        let parser = choice!(and!(char('a'), char('b')), and!(char('a'), char('c')));
+        let response = parser.parse(b"ac", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('a', 'c'), || false), true);
+    }
+
+    #[test]
+    fn it_dispatch_on_any_combinator() {
+        let parser = choice!(
+            map(char('a'), |_| 1),
+            spanned(char('b')).map(|(_, r)| r.start),
+            label("c", cut(char('c'))).map(|_| 3),
+            escaped_string(b'"').map(|v| v.len())
+        );
+        let response = parser.parse(b"\"dd\"", 0);
+
+        assert_eq!(response.fold(|v, _| v == 2, || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_nullable_alternative_at_the_end() {
+        let parser = choice!(rep!(char('a')), optrep!(char('b')));
+        let response = parser.parse(b"", 0);
+
+        assert_eq!(response.fold(|v, _| v.is_empty(), || false), true);
+    }
+}
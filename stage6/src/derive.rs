@@ -0,0 +1,328 @@
+//
+// Support for #[derive(Parse)] i.e. types which know their own parser
+//
+
+use std::marker::PhantomData;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::report;
+use crate::stream;
+use crate::Check;
+use crate::Combine;
+use crate::Map;
+use crate::Opt;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Grammar trait
+//
+
+pub trait Grammar: Sized {
+    type Parser: for<'a> Parse<'a, Self> + Check + Combine<Self>;
+
+    fn parser() -> Self::Parser;
+}
+
+impl<T> Grammar for Option<T>
+where
+    T: Grammar,
+{
+    type Parser = Opt<T::Parser, T>;
+
+    fn parser() -> Self::Parser {
+        Opt(T::parser(), PhantomData)
+    }
+}
+
+impl<T> Grammar for Box<T>
+where
+    T: Grammar,
+{
+    type Parser = Map<T::Parser, fn(T) -> Box<T>, T>;
+
+    fn parser() -> Self::Parser {
+        Map(T::parser(), Box::new, PhantomData)
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Leaf parsers
+//
+
+pub struct Integer;
+
+impl Combine<i64> for Integer {}
+
+impl<'a> Parse<'a, i64> for Integer {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<i64> {
+        match self.check(s, o) {
+            Success(_, e) => match std::str::from_utf8(&s[o..e]).unwrap().parse() {
+                Ok(v) => Success(v, e),
+                Err(_) => Reject,
+            },
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl Check for Integer {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let start = if s.get(o) == Some(&b'-') { o + 1 } else { o };
+        let end = scan(s, start, |b| b.is_ascii_digit());
+
+        // The digits may continue after the end of a partial input
+        if end >= s.len() && stream::is_partial() {
+            return Incomplete(1);
+        }
+
+        if end > start {
+            Success((), end)
+        } else {
+            Reject
+        }
+    }
+}
+
+impl First for Integer {
+    fn first(&self) -> FirstSet {
+        (b'0'..=b'9').fold(FirstSet::byte(b'-'), |set, b| set.union(FirstSet::byte(b)))
+    }
+}
+
+impl Grammar for i64 {
+    type Parser = Integer;
+
+    fn parser() -> Self::Parser {
+        Integer
+    }
+}
+
+pub struct Identifier;
+
+impl Combine<String> for Identifier {}
+
+impl<'a> Parse<'a, String> for Identifier {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<String> {
+        match self.check(s, o) {
+            Success(_, e) => Success(String::from_utf8_lossy(&s[o..e]).into_owned(), e),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl Check for Identifier {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        match s.get(o) {
+            Some(b) if b.is_ascii_alphabetic() || *b == b'_' => {
+                let end = scan(s, o, |b| b.is_ascii_alphanumeric() || b == b'_');
+
+                // The identifier may continue after the end of a partial input
+                if end == s.len() && stream::is_partial() {
+                    return Incomplete(1);
+                }
+
+                Success((), end)
+            }
+            Some(_) => Reject,
+            None => stream::end_of_input(1),
+        }
+    }
+}
+
+impl First for Identifier {
+    fn first(&self) -> FirstSet {
+        let mut set = FirstSet::byte(b'_');
+        for b in (b'a'..=b'z').chain(b'A'..=b'Z') {
+            set.bytes[b as usize] = true;
+        }
+        set
+    }
+}
+
+impl Grammar for String {
+    type Parser = Identifier;
+
+    fn parser() -> Self::Parser {
+        Identifier
+    }
+}
+
+fn scan<F>(s: &[u8], o: usize, f: F) -> usize
+where
+    F: Fn(u8) -> bool,
+{
+    o + s[o.min(s.len())..].iter().take_while(|b| f(**b)).count()
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Helpers used by the generated code. Whitespaces are skipped before each element.
+//
+
+pub fn spaces(s: &[u8], o: usize) -> usize {
+    scan(s, o, |b| b.is_ascii_whitespace())
+}
+
+// A token ending with an identifier character cannot be followed by one e.g. `let` in `letx`
+
+pub fn token(t: &[u8], s: &[u8], o: usize) -> Response<()> {
+    let o = spaces(s, o);
+    let e = o + t.len();
+    let rest = &s[o.min(s.len())..];
+    let word = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_';
+
+    // The token or the word it ends may continue after the end of a partial input
+    if stream::is_partial() {
+        if rest.len() < t.len() && t.starts_with(rest) {
+            return Incomplete(t.len() - rest.len());
+        }
+        if rest == t && t.last().is_some_and(word) {
+            return Incomplete(1);
+        }
+    }
+
+    if !rest.starts_with(t) || (t.last().is_some_and(word) && s.get(e).is_some_and(word)) {
+        report::reject_with(o, || vec![format!("\"{}\"", String::from_utf8_lossy(t))])
+    } else {
+        Success((), e)
+    }
+}
+
+pub fn field<T>(s: &[u8], o: usize) -> Response<T>
+where
+    T: Grammar,
+{
+    T::parser().parse(s, spaces(s, o))
+}
+
+pub fn check_field<T>(s: &[u8], o: usize) -> Response<()>
+where
+    T: Grammar,
+{
+    T::parser().check(s, spaces(s, o))
+}
+
+pub fn many<T>(s: &[u8], o: usize, sep: Option<&[u8]>) -> Response<Vec<T>>
+where
+    T: Grammar,
+{
+    let mut values = Vec::new();
+    let mut offset = o;
+
+    loop {
+        match next(s, offset, !values.is_empty(), sep, field::<T>) {
+            // An element consuming nothing would be repeated forever
+            Success(_, o) if o == offset => return Success(values, offset),
+            Success(v, o) => {
+                values.push(v);
+                offset = o;
+            }
+            Reject => return Success(values, offset),
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        }
+    }
+}
+
+pub fn check_many<T>(s: &[u8], o: usize, sep: Option<&[u8]>) -> Response<()>
+where
+    T: Grammar,
+{
+    let mut offset = o;
+    let mut first = true;
+
+    loop {
+        match next(s, offset, !first, sep, check_field::<T>) {
+            Success(_, o) if o == offset => return Success((), offset),
+            Success(_, o) => {
+                offset = o;
+                first = false;
+            }
+            Reject => return Success((), offset),
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        }
+    }
+}
+
+fn next<V, F>(s: &[u8], o: usize, separated: bool, sep: Option<&[u8]>, f: F) -> Response<V>
+where
+    F: Fn(&[u8], usize) -> Response<V>,
+{
+    match sep {
+        Some(sep) if separated => match token(sep, s, o) {
+            Success(_, o) => f(s, o),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        },
+        _ => f(s, o),
+    }
+}
+
+#[cfg(test)]
+mod tests_grammar {
+    use response::Response::Incomplete;
+
+    use crate::derive::{token, Grammar};
+    use crate::stream::partial;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_an_integer() {
+        let response = i64::parser().parse(b"-42", 0);
+
+        assert_eq!(response.fold(|v, s| v == -42 && s == 3, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_an_integer() {
+        let response = i64::parser().parse(b"-", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_parse_an_identifier() {
+        let response = String::parser().parse(b"a_1 ", 0);
+
+        assert_eq!(response.fold(|v, s| v == "a_1" && s == 3, || false), true);
+    }
+
+    #[test]
+    fn it_ask_for_the_rest_of_a_partial_integer() {
+        let response = partial(|| i64::parser().parse(b"42", 0));
+
+        assert_eq!(matches!(response, Incomplete(1)), true);
+    }
+
+    #[test]
+    fn it_ask_for_the_rest_of_a_partial_identifier() {
+        let response = partial(|| String::parser().parse(b"a_1", 0));
+
+        assert_eq!(matches!(response, Incomplete(1)), true);
+    }
+
+    #[test]
+    fn it_ask_for_the_rest_of_a_partial_token() {
+        let response = partial(|| token(b"let", b" le", 0));
+
+        assert_eq!(matches!(response, Incomplete(1)), true);
+    }
+
+    #[test]
+    fn it_parse_an_optional_boxed_integer() {
+        let response = <Option<Box<i64>>>::parser().parse(b"1", 0);
+
+        assert_eq!(response.fold(|v, _| v == Some(Box::new(1)), || false), true);
+    }
+}
@@ -0,0 +1,157 @@
+//
+// Fluent construction i.e. combinators as methods on every parser
+//
+
+use std::marker::PhantomData;
+
+use crate::boxed::BoxedParser;
+use crate::choice::Or;
+use crate::iter::Iter;
+use crate::span::{Recognize, Spanned};
+use crate::And;
+use crate::Check;
+use crate::Combine;
+use crate::Cut;
+use crate::Frame;
+use crate::Label;
+use crate::Map;
+use crate::Opt;
+use crate::Parse;
+use crate::Repeat;
+use crate::Skip;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The ParserExt trait
+//
+
+pub trait ParserExt<A>: Combine<A> + Sized {
+    fn then<R, B>(self, r: R) -> And<Self, R, A, B>
+    where
+        R: Combine<B>,
+    {
+        And(self, r, PhantomData, PhantomData)
+    }
+
+    fn or<R>(self, r: R) -> Or<Self, R, A>
+    where
+        R: Combine<A>,
+    {
+        Or(self, r, PhantomData)
+    }
+
+    fn map<F, B>(self, f: F) -> Map<Self, F, A>
+    where
+        F: Fn(A) -> B,
+    {
+        Map(self, f, PhantomData)
+    }
+
+    fn many(self) -> Repeat<Self, A> {
+        Repeat(true, self, PhantomData)
+    }
+
+    fn many1(self) -> Repeat<Self, A> {
+        Repeat(false, self, PhantomData)
+    }
+
+    fn opt(self) -> Opt<Self, A> {
+        Opt(self, PhantomData)
+    }
+
+    fn skip<R, B>(self, r: R) -> Skip<Self, R, A, B>
+    where
+        R: Combine<B>,
+    {
+        Skip(self, r, PhantomData, PhantomData)
+    }
+
+    fn cut(self) -> Cut<Self, A> {
+        Cut(self, PhantomData)
+    }
+
+    fn label(self, name: &'static str) -> Label<Self, A> {
+        Label(self, name, PhantomData)
+    }
+
+    fn context(self, name: &'static str) -> Frame<Self, A> {
+        Frame(self, name, PhantomData)
+    }
+
+    fn spanned(self) -> Spanned<Self, A> {
+        Spanned(self, PhantomData)
+    }
+
+    fn recognize(self) -> Recognize<Self, A> {
+        Recognize(self, PhantomData)
+    }
+
+    fn boxed<'a>(self) -> BoxedParser<'a, A>
+    where
+        Self: Parse<'a, A> + Check + 'a,
+    {
+        BoxedParser(Box::new(self))
+    }
+
+    fn iter(self, s: &[u8]) -> Iter<'_, Self, A> {
+        Iter::new(self, s)
+    }
+}
+
+impl<P, A> ParserExt<A> for P where P: Combine<A> {}
+
+#[cfg(test)]
+mod tests_parser_ext {
+    use crate::char;
+    use crate::ext::ParserExt;
+    use crate::not;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_sequence() {
+        let response = char('a').then(char('b')).parse(b"ab", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('a', 'b'), || false), true);
+    }
+
+    #[test]
+    fn it_parse_an_alternative() {
+        let response = char('a').or(char('b')).parse(b"b", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'b', || false), true);
+    }
+
+    #[test]
+    fn it_parse_and_map_a_repetition() {
+        let parser = char('a').many1().map(|v| v.len());
+        let response = parser.parse(b"aaab", 0);
+
+        assert_eq!(response.fold(|v, _| v == 3, || false), true);
+    }
+
+    #[test]
+    fn it_parse_nothing() {
+        let response = char('a').many().parse(b"b", 0);
+
+        assert_eq!(response.fold(|v, _| v.is_empty(), || false), true);
+    }
+
+    #[test]
+    fn it_parse_an_optional_character() {
+        let response = char('-').opt().then(char('1')).parse(b"1", 0);
+
+        assert_eq!(response.fold(|v, _| v == (None, '1'), || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_delimited_string() {
+        let parser = char('"')
+            .then(not('"').many())
+            .skip(char('"'))
+            .map(|(_, v)| v.into_iter().collect::<String>())
+            .label("string");
+        let response = parser.parse(b"\"aaa\"", 0);
+
+        assert_eq!(response.fold(|v, s| v == "aaa" && s == 5, || false), true);
+    }
+}
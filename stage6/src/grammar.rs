@@ -0,0 +1,420 @@
+//
+// Declarative grammars i.e. PEG rules expanded to stage6 parsers
+//
+// grammar! {
+//     pub mod json {
+//         rule value -> Json = null / number / array;
+//         rule array -> Json = '[' elements? ']' { |(_, v, _)| ... };
+//         ...
+//     }
+// }
+//
+// Each rule becomes a unit struct in `json::rules` and a constructor `json::value()`. Rules
+// are nominal types so they can be mutually recursive; left recursion is not supported.
+//
+// - `a / b` is an ordered choice and `a b` a sequence giving a flat tuple,
+// - `a*`, `a+` and `a?` repeat or make optional, `!a` is a negative lookahead,
+// - `'c'` and `"text"` are literals, `.` is any character and `( ... )` groups,
+// - `{ |v| ... }` after a sequence is a semantic action,
+// - `{ expr }` alone is a rule defined by any stage6 parser expression.
+//
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::report;
+use crate::stream;
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The grammar! macro
+//
+
+#[macro_export]
+macro_rules! grammar {
+    // Rules
+
+    ( @rules $ head: tt [ $ ( $ done: tt )* ] rule $ r: ident -> $ ty: ty = $ ( $ rest: tt )* ) => {
+        $crate::grammar! { @body $ head [ $ ( $ done )* ] ( $ r $ ty ) [ ] $ ( $ rest )* }
+    };
+
+    ( @rules [ $ ( # [ $ attr: meta ] )* $ vis: vis mod $ name: ident ]
+      [ $ ( ( $ r: ident $ ty: ty [ $ ( $ body: tt )* ] ) )* ] ) => {
+        $ ( # [ $ attr ] )*
+        $ vis mod $ name {
+            #![allow(dead_code, unused_imports)]
+
+            use super::*;
+
+            $ (
+                pub fn $ r() -> rules::$ r {
+                    rules::$ r
+                }
+            )*
+
+            pub mod rules {
+                #![allow(non_camel_case_types)]
+
+                use super::super::*;
+
+                $ (
+                    pub struct $ r;
+
+                    impl $crate::Combine<$ ty> for $ r {}
+
+                    impl<'a> $crate::Parse<'a, $ ty> for $ r {
+                        fn parse(&self, s: &'a [u8], o: usize) -> $crate::Response<$ ty> {
+                            let body = $crate::grammar::rule::<$ ty, _>(
+                                $crate::grammar!(@choice [ ] [ ] $ ( $ body )*)
+                            );
+
+                            $crate::Parse::<'a, $ ty>::parse(&body, s, o)
+                        }
+                    }
+
+                    impl $crate::Check for $ r {
+                        fn check(&self, s: &[u8], o: usize) -> $crate::Response<()> {
+                            let body = $crate::grammar::rule::<$ ty, _>(
+                                $crate::grammar!(@choice [ ] [ ] $ ( $ body )*)
+                            );
+
+                            $crate::Check::check(&body, s, o)
+                        }
+                    }
+                )*
+            }
+        }
+    };
+
+    ( @body $ head: tt [ $ ( $ done: tt )* ] ( $ r: ident $ ty: ty ) [ $ ( $ acc: tt )* ]
+      ; $ ( $ rest: tt )* ) => {
+        $crate::grammar! { @rules $ head [ $ ( $ done )* ( $ r $ ty [ $ ( $ acc )* ] ) ] $ ( $ rest )* }
+    };
+
+    ( @body $ head: tt [ $ ( $ done: tt )* ] $ rule: tt [ $ ( $ acc: tt )* ]
+      $ t: tt $ ( $ rest: tt )* ) => {
+        $crate::grammar! { @body $ head [ $ ( $ done )* ] $ rule [ $ ( $ acc )* $ t ] $ ( $ rest )* }
+    };
+
+    // Choices i.e. sequences separated by /
+
+    ( @choice [ $ ( $ alts: tt )* ] [ $ ( $ cur: tt )* ] / $ ( $ rest: tt )* ) => {
+        $crate::grammar!(@choice [ $ ( $ alts )* [ $ ( $ cur )* ] ] [ ] $ ( $ rest )*)
+    };
+
+    ( @choice [ $ ( $ alts: tt )* ] [ $ ( $ cur: tt )* ] $ t: tt $ ( $ rest: tt )* ) => {
+        $crate::grammar!(@choice [ $ ( $ alts )* ] [ $ ( $ cur )* $ t ] $ ( $ rest )*)
+    };
+
+    ( @choice [ ] [ $ ( $ cur: tt )* ] ) => {
+        $crate::grammar!(@seq [ ] $ ( $ cur )*)
+    };
+
+    ( @choice [ $ ( [ $ ( $ alt: tt )* ] )+ ] [ $ ( $ cur: tt )* ] ) => {
+        $crate::alt!(
+            $ ( $crate::grammar!(@seq [ ] $ ( $ alt )*), )+
+            $crate::grammar!(@seq [ ] $ ( $ cur )*)
+        )
+    };
+
+    // Sequences i.e. prefixed or suffixed primaries and a final action
+
+    ( @seq [ ] { $ ( $ e: tt )* } ) => {
+        { $ ( $ e )* }
+    };
+
+    ( @seq [ $ ( $ items: expr, )+ ] { $ ( $ action: tt )* } ) => {
+        $crate::map($crate::grammar!(@seq [ $ ( $ items, )+ ]), $ ( $ action )*)
+    };
+
+    ( @seq [ $ ( $ items: expr, )* ] ! $ p: tt $ ( $ rest: tt )* ) => {
+        $crate::grammar!(@seq [
+            $ ( $ items, )*
+            $crate::ops::NotFollowedBy($crate::grammar!(@primary $ p), ::std::marker::PhantomData),
+        ] $ ( $ rest )*)
+    };
+
+    ( @seq [ $ ( $ items: expr, )* ] $ p: tt * $ ( $ rest: tt )* ) => {
+        $crate::grammar!(@seq [
+            $ ( $ items, )*
+            $crate::optrep!($crate::grammar!(@primary $ p)),
+        ] $ ( $ rest )*)
+    };
+
+    ( @seq [ $ ( $ items: expr, )* ] $ p: tt + $ ( $ rest: tt )* ) => {
+        $crate::grammar!(@seq [
+            $ ( $ items, )*
+            $crate::rep!($crate::grammar!(@primary $ p)),
+        ] $ ( $ rest )*)
+    };
+
+    ( @seq [ $ ( $ items: expr, )* ] $ p: tt ? $ ( $ rest: tt )* ) => {
+        $crate::grammar!(@seq [
+            $ ( $ items, )*
+            $crate::Opt($crate::grammar!(@primary $ p), ::std::marker::PhantomData),
+        ] $ ( $ rest )*)
+    };
+
+    ( @seq [ $ ( $ items: expr, )* ] $ p: tt $ ( $ rest: tt )* ) => {
+        $crate::grammar!(@seq [ $ ( $ items, )* $crate::grammar!(@primary $ p), ] $ ( $ rest )*)
+    };
+
+    ( @seq [ $ item: expr, ] ) => {
+        $ item
+    };
+
+    ( @seq [ $ ( $ items: expr, )+ ] ) => {
+        $crate::seq!( $ ( $ items ),+ )
+    };
+
+    // Primaries
+
+    ( @primary ( $ ( $ e: tt )* ) ) => {
+        $crate::grammar!(@choice [ ] [ ] $ ( $ e )*)
+    };
+
+    ( @primary . ) => {
+        $crate::any()
+    };
+
+    ( @primary $ l: literal ) => {
+        $crate::grammar::Literal($ l)
+    };
+
+    ( @primary $ r: ident ) => {
+        $ r
+    };
+
+    // Entry point
+
+    ( $ ( # [ $ attr: meta ] )* $ vis: vis mod $ name: ident { $ ( $ rules: tt )* } ) => {
+        $crate::grammar! { @rules [ $ ( # [ $ attr ] )* $ vis mod $ name ] [ ] $ ( $ rules )* }
+    };
+}
+
+// Fixes the output type of a rule body i.e. semantic actions are inferred from it
+
+pub fn rule<A, P>(p: P) -> P
+where
+    P: Combine<A>,
+{
+    p
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Literal parser
+//
+
+pub struct Literal<T>(pub T);
+
+impl Combine<char> for Literal<char> {}
+
+impl<'a> Parse<'a, char> for Literal<char> {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<char> {
+        let Literal(c) = self;
+        let expected = || vec![format!("'{}'", c.escape_default())];
+
+        // The character is matched against its UTF-8 encoding
+        let mut buffer = [0; 4];
+        let t = c.encode_utf8(&mut buffer).as_bytes();
+        let rest = &s[o.min(s.len())..];
+
+        if rest.starts_with(t) {
+            Success(*c, o + t.len())
+        } else if t.starts_with(rest) {
+            report::track_with(o, expected, stream::end_of_input(t.len() - rest.len()))
+        } else {
+            report::reject_with(o, expected)
+        }
+    }
+}
+
+impl Check for Literal<char> {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        match self.parse(s, o) {
+            Success(_, s) => Success((), s),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl First for Literal<char> {
+    fn first(&self) -> FirstSet {
+        let Literal(c) = self;
+        let mut buffer = [0; 4];
+
+        FirstSet::byte(c.encode_utf8(&mut buffer).as_bytes()[0])
+    }
+}
+
+impl Combine<&'static str> for Literal<&'static str> {}
+
+impl<'a> Parse<'a, &'static str> for Literal<&'static str> {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<&'static str> {
+        let Literal(t) = self;
+
+        let rest = &s[o.min(s.len())..];
+        let expected = || vec![format!("\"{}\"", t.escape_default())];
+
+        if rest.starts_with(t.as_bytes()) {
+            Success(*t, o + t.len())
+        } else if t.as_bytes().starts_with(rest) {
+            report::track_with(o, expected, stream::end_of_input(t.len() - rest.len()))
+        } else {
+            report::reject_with(o, expected)
+        }
+    }
+}
+
+impl Check for Literal<&'static str> {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        match self.parse(s, o) {
+            Success(_, s) => Success((), s),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl First for Literal<&'static str> {
+    fn first(&self) -> FirstSet {
+        let Literal(t) = self;
+
+        match t.as_bytes().first() {
+            Some(b) => FirstSet::byte(*b),
+            None => FirstSet::all(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_grammar {
+    use crate::choice::First;
+    use crate::grammar::Literal;
+    use crate::report::parse;
+    use crate::Check;
+    use crate::Parse;
+    use crate::Satisfy;
+
+    #[derive(Debug, PartialEq)]
+    enum Json {
+        Null,
+        Bool(bool),
+        Number(i64),
+        Array(Vec<Json>),
+    }
+
+    grammar! {
+        mod json {
+            rule value -> Json = null / boolean / number / array;
+            rule null -> Json = "null" { |_| Json::Null };
+            rule boolean -> Json = "true" { |_| Json::Bool(true) } / "false" { |_| Json::Bool(false) };
+            rule number -> Json = '-'? digit+ {
+                |(s, v): (Option<char>, Vec<char>)| {
+                    let n = v.into_iter().collect::<String>().parse::<i64>().unwrap();
+                    Json::Number(if s.is_some() { -n } else { n })
+                }
+            };
+            rule digit -> char = { Satisfy(|c: char| c.is_ascii_digit()) };
+            rule array -> Json = '[' elements? ']' {
+                |(_, v, _): (char, Option<Vec<Json>>, char)| Json::Array(v.unwrap_or_default())
+            };
+            rule elements -> Vec<Json> = value (',' value)* {
+                |(v, vs): (Json, Vec<(char, Json)>)| {
+                    let mut values = vec![v];
+                    values.extend(vs.into_iter().map(|(_, v)| v));
+                    values
+                }
+            };
+            rule comment -> () = "/*" (!"*/" .)* "*/" { |_| () };
+        }
+    }
+
+    #[test]
+    fn it_parse_a_literal_rule() {
+        let response = json::value().parse(b"null", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == Json::Null && s == 4, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_parse_an_ordered_choice() {
+        let response = json::value().parse(b"false", 0);
+
+        assert_eq!(response.fold(|v, _| v == Json::Bool(false), || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_negative_number() {
+        let response = json::value().parse(b"-42", 0);
+
+        assert_eq!(response.fold(|v, _| v == Json::Number(-42), || false), true);
+    }
+
+    #[test]
+    fn it_parse_mutually_recursive_rules() {
+        let response = json::value().parse(b"[1,[],[true,null]]", 0);
+
+        assert_eq!(
+            response.fold(
+                |v, s| v
+                    == Json::Array(vec![
+                        Json::Number(1),
+                        Json::Array(vec![]),
+                        Json::Array(vec![Json::Bool(true), Json::Null])
+                    ])
+                    && s == 18,
+                || false
+            ),
+            true
+        );
+    }
+
+    #[test]
+    fn it_cannot_parse_an_unterminated_array() {
+        let response = json::value().parse(b"[1,2", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_report_the_expected_literals() {
+        let report = parse(&json::value(), b"[1;").unwrap_err();
+
+        assert_eq!(report.message(), "expected ',' or ']', found ';' at 1:3");
+    }
+
+    #[test]
+    fn it_check_mutually_recursive_rules() {
+        let response = json::array().check(b"[[1],2]", 0);
+
+        assert_eq!(response.fold(|_, s| s == 7, || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_negative_lookahead() {
+        let response = json::comment().check(b"/* a * b */", 0);
+
+        assert_eq!(response.fold(|_, s| s == 11, || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_multibyte_literal_character() {
+        let response = Literal('é').parse("é".as_bytes(), 0);
+
+        assert_eq!(response.fold(|v, s| v == 'é' && s == 2, || false), true);
+        assert_eq!(Literal('é').first().bytes[0xC3], true);
+        assert_eq!(Literal('é').first().bytes[0xE9], false);
+    }
+}
@@ -0,0 +1,456 @@
+//
+// Indentation sensitive parsing i.e. columns computed from the input
+//
+// The reference indentation is the one of the line containing the current offset: no state
+// is required. Spaces and tabs count for one column each, lines end with `\n` or `\r\n` and
+// blank lines are ignored. In partial mode the next line is awaited at the end of the input.
+//
+
+use std::marker::PhantomData;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::stream;
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Lines and columns
+//
+
+// Line and column of an offset, both starting at 1
+
+pub fn position(s: &[u8], o: usize) -> (usize, usize) {
+    let o = o.min(s.len());
+    let line = s[..o].iter().filter(|c| **c == b'\n').count() + 1;
+
+    (line, column(s, o) + 1)
+}
+
+fn line_start(s: &[u8], o: usize) -> usize {
+    s[..o.min(s.len())]
+        .iter()
+        .rposition(|c| *c == b'\n')
+        .map_or(0, |p| p + 1)
+}
+
+fn column(s: &[u8], o: usize) -> usize {
+    o - line_start(s, o)
+}
+
+fn blanks(s: &[u8], o: usize) -> usize {
+    o + s[o.min(s.len())..]
+        .iter()
+        .take_while(|c| **c == b' ' || **c == b'\t')
+        .count()
+}
+
+fn indentation(s: &[u8], o: usize) -> usize {
+    let start = line_start(s, o);
+
+    blanks(s, start) - start
+}
+
+// The offset following a line end at o
+
+fn line_end(s: &[u8], o: usize) -> Response<usize> {
+    match s.get(o..) {
+        Some([b'\n', ..]) => Success(o + 1, o + 1),
+        Some([b'\r', b'\n', ..]) => Success(o + 2, o + 2),
+        Some([] | [b'\r']) => stream::end_of_input(1),
+        _ => Reject,
+    }
+}
+
+// The first character of the next non blank line when o ends the current one
+
+fn next_line(s: &[u8], o: usize) -> Response<usize> {
+    let mut offset = match line_end(s, blanks(s, o)) {
+        Success(n, _) => blanks(s, n),
+        Reject => return Reject,
+        Incomplete(n) => return Incomplete(n),
+        Fatal => return Fatal,
+    };
+
+    loop {
+        match line_end(s, offset) {
+            Success(n, _) => offset = blanks(s, n),
+            Reject if offset < s.len() => return Success(offset, offset),
+            Reject => return Reject,
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_position {
+    use crate::indent::position;
+
+    #[test]
+    fn it_compute_a_position() {
+        assert_eq!(position(b"ab\n  cd", 5), (2, 3));
+    }
+
+    #[test]
+    fn it_compute_the_first_position() {
+        assert_eq!(position(b"ab", 0), (1, 1));
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Indented and SameIndent parsers i.e. a parser starting the next line
+//
+
+pub struct Indented<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn indented<P, A>(p: P) -> Indented<P, A>
+where
+    P: Combine<A>,
+{
+    Indented(p, PhantomData)
+}
+
+impl<P, A> Combine<A> for Indented<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, A> for Indented<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let Indented(p, _) = self;
+
+        match next_line(s, o) {
+            Success(n, _) if column(s, n) > indentation(s, o) => p.parse(s, n),
+            Success(_, _) | Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<P, A> Check for Indented<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Indented(p, _) = self;
+
+        match next_line(s, o) {
+            Success(n, _) if column(s, n) > indentation(s, o) => p.check(s, n),
+            Success(_, _) | Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+// The parser starts on another line i.e. after any byte
+
+impl<P, A> First for Indented<P, A>
+where
+    P: Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+pub struct SameIndent<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn same_indent<P, A>(p: P) -> SameIndent<P, A>
+where
+    P: Combine<A>,
+{
+    SameIndent(p, PhantomData)
+}
+
+impl<P, A> Combine<A> for SameIndent<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, A> for SameIndent<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let SameIndent(p, _) = self;
+
+        match next_line(s, o) {
+            Success(n, _) if column(s, n) == indentation(s, o) => p.parse(s, n),
+            Success(_, _) | Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<P, A> Check for SameIndent<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let SameIndent(p, _) = self;
+
+        match next_line(s, o) {
+            Success(n, _) if column(s, n) == indentation(s, o) => p.check(s, n),
+            Success(_, _) | Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+// The parser starts on another line i.e. after any byte
+
+impl<P, A> First for SameIndent<P, A>
+where
+    P: Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_indented {
+    use response::Response::Incomplete;
+
+    use crate::char;
+    use crate::indent::{indented, same_indent};
+    use crate::stream::partial;
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_an_indented_line() {
+        let response = and!(char('a'), indented(char('b'))).parse(b"a\n\n  b", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == ('a', 'b') && s == 6, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_cannot_parse_a_line_without_indentation() {
+        let response = and!(char('a'), indented(char('b'))).parse(b"a\nb", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_on_the_same_line() {
+        let response = and!(char('a'), indented(char('b'))).check(b"a  b", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_parse_a_line_with_the_same_indentation() {
+        let response = and!(char('a'), same_indent(char('b'))).parse(b" a \n b", 1);
+
+        assert_eq!(response.fold(|v, _| v == ('a', 'b'), || false), true);
+    }
+
+    #[test]
+    fn it_parse_an_indented_line_after_a_crlf() {
+        let response = and!(char('a'), indented(char('b'))).parse(b"a \r\n\r\n  b", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == ('a', 'b') && s == 9, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_await_the_next_line_of_a_partial_input() {
+        let response = partial(|| and!(char('a'), indented(char('b'))).parse(b"a\r\n", 0));
+
+        assert_eq!(matches!(response, Incomplete(1)), true);
+    }
+
+    #[test]
+    fn it_cannot_check_a_line_with_another_indentation() {
+        let response = and!(char('a'), same_indent(char('b'))).check(b"a\n b", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Block parser i.e. lines aligned on the first one
+//
+
+pub struct Block<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn block<P, A>(p: P) -> Block<P, A>
+where
+    P: Combine<A>,
+{
+    Block(p, PhantomData)
+}
+
+impl<P, A> Combine<Vec<A>> for Block<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, Vec<A>> for Block<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<Vec<A>> {
+        let Block(p, _) = self;
+
+        let start = blanks(s, o);
+        let alignment = column(s, start);
+
+        let (mut values, mut offset) = match p.parse(s, start) {
+            Success(v, s) => (vec![v], s),
+            Reject => return Reject,
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        };
+
+        loop {
+            let n = match next_line(s, offset) {
+                Success(n, _) if column(s, n) == alignment => n,
+                Success(_, _) | Reject => break,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            };
+
+            match p.parse(s, n) {
+                Success(v, s) => {
+                    values.push(v);
+                    offset = s;
+                }
+                Reject => break,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Success(values, offset)
+    }
+}
+
+impl<P, A> Check for Block<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Block(p, _) = self;
+
+        let start = blanks(s, o);
+        let alignment = column(s, start);
+
+        let mut offset = match p.check(s, start) {
+            Success(_, s) => s,
+            Reject => return Reject,
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        };
+
+        loop {
+            let n = match next_line(s, offset) {
+                Success(n, _) if column(s, n) == alignment => n,
+                Success(_, _) | Reject => break,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            };
+
+            match p.check(s, n) {
+                Success(_, s) => offset = s,
+                Reject => break,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Success((), offset)
+    }
+}
+
+// The parser starts on another line i.e. after any byte
+
+impl<P, A> First for Block<P, A>
+where
+    P: Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_block {
+    use crate::choice::Or;
+    use crate::indent::{block, indented};
+    use crate::map;
+    use crate::Check;
+    use crate::Combine;
+    use crate::Parse;
+    use crate::Satisfy;
+
+    // Statements are names or `if` followed by an indented block of names
+
+    fn statements<'a>() -> impl Parse<'a, Vec<String>> + Check + Combine<Vec<String>> {
+        let name = || Satisfy(|c: char| c.is_ascii_lowercase() && c != 'i');
+        let header = and!(crate::char('i'), crate::char('f'));
+        let condition = map(and!(header, indented(block(name()))), |(_, v)| {
+            format!("if({})", v.into_iter().collect::<String>())
+        });
+
+        block(Or(
+            condition,
+            map(name(), |c| c.to_string()),
+            Default::default(),
+        ))
+    }
+
+    #[test]
+    fn it_parse_nested_blocks() {
+        let response = statements().parse(b"a\nif\n  b\n\n  c\nd", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == vec!["a", "if(bc)", "d"] && s == 15, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_stop_on_a_dedent() {
+        let response = statements().parse(b"  a\n  b\nc", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == vec!["a", "b"] && s == 7, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_parse_nested_blocks_with_crlf() {
+        let response = statements().parse(b"a\r\nif\r\n  b\r\n  c\r\nd", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == vec!["a", "if(bc)", "d"] && s == 18, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_check_nested_blocks() {
+        let response = statements().check(b"if\n b\n c\nd\n", 0);
+
+        assert_eq!(response.fold(|_, s| s == 10, || false), true);
+    }
+}
@@ -0,0 +1,145 @@
+//
+// Record streams i.e. values parsed one at a time instead of a whole repetition
+//
+
+use std::marker::PhantomData;
+
+use response::Response::Success;
+
+use crate::Combine;
+use crate::Parse;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Iter iterator
+//
+
+// Iteration stops at the end of the input or at the first record which cannot be parsed. A
+// record consuming nothing stops the iteration as well since it would be repeated forever.
+
+pub struct Iter<'a, P, A>
+where
+    P: Combine<A>,
+{
+    parser: P,
+    input: &'a [u8],
+    offset: usize,
+    failed: bool,
+    _a: PhantomData<A>,
+}
+
+impl<'a, P, A> Iter<'a, P, A>
+where
+    P: Combine<A>,
+{
+    pub fn new(parser: P, input: &'a [u8]) -> Self {
+        Iter {
+            parser,
+            input,
+            offset: 0,
+            failed: false,
+            _a: PhantomData,
+        }
+    }
+
+    // The offset following the last parsed record
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn finish(self) -> Finish {
+        if self.failed {
+            Finish::Failed(self.offset)
+        } else if self.offset < self.input.len() {
+            Finish::Stopped(self.offset)
+        } else {
+            Finish::Complete(self.offset)
+        }
+    }
+}
+
+// How an iteration ended, with the offset following the last parsed record
+
+#[derive(Debug, PartialEq)]
+pub enum Finish {
+    Complete(usize), // The input is consumed
+    Stopped(usize),  // The iteration was left before the end of the input
+    Failed(usize),   // No record can be parsed at this offset
+}
+
+impl<'a, P, A> Iterator for Iter<'a, P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    type Item = A;
+
+    fn next(&mut self) -> Option<A> {
+        if self.failed || self.offset >= self.input.len() {
+            return None;
+        }
+
+        match self.parser.parse(self.input, self.offset) {
+            Success(v, o) if o > self.offset => {
+                self.offset = o;
+                Some(v)
+            }
+            _ => {
+                self.failed = true;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_iter {
+    use crate::char;
+    use crate::ext::ParserExt;
+    use crate::iter::Finish;
+    use crate::not;
+    use crate::Combine;
+    use crate::Parse;
+
+    fn record() -> impl for<'a> Parse<'a, String> + Combine<String> {
+        not('\n')
+            .many1()
+            .skip(char('\n'))
+            .map(|v| v.into_iter().collect())
+    }
+
+    #[test]
+    fn it_iterate_over_records() {
+        let mut records = record().iter(b"a\nbb\nccc\n");
+        let values: Vec<String> = records.by_ref().collect();
+
+        assert_eq!(values, vec!["a", "bb", "ccc"]);
+        assert_eq!(records.finish(), Finish::Complete(9));
+    }
+
+    #[test]
+    fn it_stop_early() {
+        let mut records = record().iter(b"a\nbb\nccc\n");
+        let first = records.next();
+
+        assert_eq!(first, Some(String::from("a")));
+        assert_eq!(records.offset(), 2);
+        assert_eq!(records.finish(), Finish::Stopped(2));
+    }
+
+    #[test]
+    fn it_expose_the_failing_offset() {
+        let mut records = record().iter(b"a\nbb\n\nccc\n");
+
+        assert_eq!(records.by_ref().count(), 2);
+        assert_eq!(records.finish(), Finish::Failed(5));
+    }
+
+    #[test]
+    fn it_iterate_over_an_empty_input() {
+        let mut records = record().iter(b"");
+
+        assert_eq!(records.next(), None);
+        assert_eq!(records.finish(), Finish::Complete(0));
+    }
+}
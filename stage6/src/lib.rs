@@ -6,11 +6,13 @@
 
 use std::marker::PhantomData;
 
-use response::Response::{Reject, Success};
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::First;
 
 //  ------------------------------------------------------------------------------------------------
 
-type Response<A> = response::Response<A, usize>;
+pub type Response<A> = response::Response<A, usize>;
 
 //  ------------------------------------------------------------------------------------------------
 // Separate type from behaviors
@@ -25,6 +27,28 @@ pub trait Check {
     fn check(&self, s: &[u8], o: usize) -> Response<()>;
 }
 
+// Shared parsers i.e. a parser can be used many times in a grammar by reference
+
+impl<P, A> Combine<A> for &P where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, A> for &P
+where
+    P: Parse<'a, A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        (*self).parse(s, o)
+    }
+}
+
+impl<P> Check for &P
+where
+    P: Check,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        (*self).check(s, o)
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 //
 // The Satisfy parser
@@ -49,9 +73,11 @@ where
             if f(c) {
                 return Success(c, o + 1);
             }
+
+            return report::reject_with(o, || report::bytes(&self.first()));
         }
 
-        Reject
+        report::track_with(o, || report::bytes(&self.first()), stream::end_of_input(1))
     }
 }
 
@@ -63,19 +89,21 @@ where
         match self.parse(s, o) {
             Success(_, s) => Success((), s),
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
 
-fn any() -> Satisfy<impl Fn(char) -> bool> {
+pub fn any() -> Satisfy<impl Fn(char) -> bool> {
     Satisfy(|_| true)
 }
 
-fn char(c: char) -> Satisfy<impl Fn(char) -> bool> {
+pub fn char(c: char) -> Satisfy<impl Fn(char) -> bool> {
     Satisfy(move |v| v == c)
 }
 
-fn not(c: char) -> Satisfy<impl Fn(char) -> bool> {
+pub fn not(c: char) -> Satisfy<impl Fn(char) -> bool> {
     Satisfy(move |v| v != c)
 }
 
@@ -139,9 +167,15 @@ where
     L: Combine<A>,
     R: Combine<B>;
 
+#[macro_export]
 macro_rules! and {
     ( $ a: expr, $ b: expr) => {
-        And($a, $b, PhantomData, PhantomData)
+        $crate::And(
+            $a,
+            $b,
+            ::std::marker::PhantomData,
+            ::std::marker::PhantomData,
+        )
     };
 }
 
@@ -164,8 +198,12 @@ where
             Success(v1, s1) => match right.parse(s, s1) {
                 Success(v2, s2) => Success((v1, v2), s2),
                 Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
             },
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -182,18 +220,19 @@ where
             Success(_, s1) => match right.check(s, s1) {
                 Success(_, s2) => Success((), s2),
                 Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
             },
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
 
 #[cfg(test)]
 mod tests_and {
-    use std::marker::PhantomData;
-
     use crate::char;
-    use crate::And;
     use crate::Parse;
 
     #[test]
@@ -223,13 +262,14 @@ where
 #[macro_export]
 macro_rules! rep {
     ( $ a: expr) => {
-        Repeat(false, $a, PhantomData)
+        $crate::Repeat(false, $a, ::std::marker::PhantomData)
     };
 }
 
+#[macro_export]
 macro_rules! optrep {
     ( $ a: expr) => {
-        Repeat(true, $a, PhantomData)
+        $crate::Repeat(true, $a, ::std::marker::PhantomData)
     };
 }
 
@@ -253,8 +293,10 @@ where
                     offset = s;
                     values.push(a);
                 }
-                _ => {
-                    if !*opt & &values.is_empty() {
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+                Reject => {
+                    if !*opt && values.is_empty() {
                         return Reject;
                     }
 
@@ -281,8 +323,10 @@ where
                 Success(_, s) => {
                     offset = s;
                 }
-                _ => {
-                    if !*opt & (offset == 0) {
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+                Reject => {
+                    if !*opt && offset == o {
                         return Reject;
                     }
 
@@ -295,11 +339,9 @@ where
 
 #[cfg(test)]
 mod tests_repeat {
-    use std::marker::PhantomData;
-
     use crate::char;
+    use crate::Check;
     use crate::Parse;
-    use crate::Repeat;
 
     #[test]
     fn it_parse_three_characters() {
@@ -321,6 +363,518 @@ mod tests_repeat {
 
         assert_eq!(response.fold(|v, _| v.is_empty(), || false), true);
     }
+
+    #[test]
+    fn it_cannot_check_a_character_after_the_start() {
+        let response = rep!(char('a')).check(b"bb", 1);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Map parser
+//
+
+pub struct Map<P, F, A>(pub P, pub F, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn map<P, F, A, B>(p: P, f: F) -> Map<P, F, A>
+where
+    P: Combine<A>,
+    F: Fn(A) -> B,
+{
+    Map(p, f, PhantomData)
+}
+
+impl<P, F, A, B> Combine<B> for Map<P, F, A>
+where
+    P: Combine<A>,
+    F: Fn(A) -> B,
+{
+}
+
+impl<'a, P, F, A, B> Parse<'a, B> for Map<P, F, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+    F: Fn(A) -> B,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<B> {
+        let Map(p, f, _) = self;
+
+        match p.parse(s, o) {
+            Success(a, s) => Success(f(a), s),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<P, F, A> Check for Map<P, F, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Map(p, _, _) = self;
+
+        p.check(s, o)
+    }
+}
+
+#[cfg(test)]
+mod tests_map {
+    use crate::char;
+    use crate::map;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_and_map_a_character() {
+        let response = map(char('a'), |c| c.to_ascii_uppercase()).parse(b"a", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'A', || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_and_map_a_character() {
+        let response = map(char('a'), |c| c.to_ascii_uppercase()).parse(b"b", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Opt parser
+//
+
+pub struct Opt<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+impl<P, A> Combine<Option<A>> for Opt<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, Option<A>> for Opt<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<Option<A>> {
+        let Opt(p, _) = self;
+
+        match p.parse(s, o) {
+            Success(a, s) => Success(Some(a), s),
+            Reject => Success(None, o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<P, A> Check for Opt<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Opt(p, _) = self;
+
+        match p.check(s, o) {
+            Success(_, s) => Success((), s),
+            Reject => Success((), o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_opt {
+    use std::marker::PhantomData;
+
+    use crate::char;
+    use crate::Opt;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_an_optional_character() {
+        let response = Opt(char('a'), PhantomData).parse(b"a", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == Some('a') && s == 1, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_parse_a_missing_optional_character() {
+        let response = Opt(char('a'), PhantomData).parse(b"b", 0);
+
+        assert_eq!(response.fold(|v, s| v.is_none() && s == 0, || false), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Skip parser
+//
+
+pub struct Skip<L, R, A, B>(pub L, pub R, pub PhantomData<A>, pub PhantomData<B>)
+where
+    L: Combine<A>,
+    R: Combine<B>;
+
+impl<L, R, A, B> Combine<A> for Skip<L, R, A, B>
+where
+    L: Combine<A>,
+    R: Combine<B>,
+{
+}
+
+impl<'a, L, R, A, B> Parse<'a, A> for Skip<L, R, A, B>
+where
+    L: Parse<'a, A> + Combine<A>,
+    R: Check + Combine<B>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let Skip(left, right, _, _) = self;
+
+        match left.parse(s, o) {
+            Success(v, s1) => match right.check(s, s1) {
+                Success(_, s2) => Success(v, s2),
+                Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
+            },
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<L, R, A, B> Check for Skip<L, R, A, B>
+where
+    L: Check + Combine<A>,
+    R: Check + Combine<B>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Skip(left, right, _, _) = self;
+
+        match left.check(s, o) {
+            Success(_, s1) => right.check(s, s1),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_skip {
+    use std::marker::PhantomData;
+
+    use crate::char;
+    use crate::Parse;
+    use crate::Skip;
+
+    #[test]
+    fn it_parse_a_character_and_skip_another() {
+        let response = Skip(char('a'), char(';'), PhantomData, PhantomData).parse(b"a;", 0);
+
+        assert_eq!(response.fold(|v, s| v == 'a' && s == 2, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_character_and_skip_another() {
+        let response = Skip(char('a'), char(';'), PhantomData, PhantomData).parse(b"a", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Label and Frame parsers
+//
+
+// A label names a parser for humans; it does not change what is recognized. When the parser is
+// rejected where it starts the label replaces what it expected in reports.
+
+pub struct Label<P, A>(pub P, pub &'static str, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn label<P, A>(name: &'static str, p: P) -> Label<P, A>
+where
+    P: Combine<A>,
+{
+    Label(p, name, PhantomData)
+}
+
+impl<P, A> Combine<A> for Label<P, A> where P: Combine<A> {}
+
+fn labelled<A>(o: usize, name: &'static str, r: &Response<A>, failure: &mut report::Failure) {
+    if matches!(r, Reject) && failure.offset == o {
+        failure.expected = vec![name.to_string()];
+    }
+}
+
+impl<'a, P, A> Parse<'a, A> for Label<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let Label(p, name, _) = self;
+
+        report::scope(o, || p.parse(s, o), |r, f| labelled(o, name, r, f))
+    }
+}
+
+impl<P, A> Check for Label<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Label(p, name, _) = self;
+
+        report::scope(o, || p.check(s, o), |r, f| labelled(o, name, r, f))
+    }
+}
+
+// A context frames the failures found inside a parser in reports e.g. "in object key: ..."
+
+pub struct Frame<P, A>(pub P, pub &'static str, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn context<P, A>(name: &'static str, p: P) -> Frame<P, A>
+where
+    P: Combine<A>,
+{
+    Frame(p, name, PhantomData)
+}
+
+impl<P, A> Combine<A> for Frame<P, A> where P: Combine<A> {}
+
+fn framed<A>(o: usize, name: &'static str, r: &Response<A>, failure: &mut report::Failure) {
+    if matches!(r, Reject) || failure.offset > o {
+        failure.context.insert(0, name);
+    }
+}
+
+impl<'a, P, A> Parse<'a, A> for Frame<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let Frame(p, name, _) = self;
+
+        report::scope(o, || p.parse(s, o), |r, f| framed(o, name, r, f))
+    }
+}
+
+impl<P, A> Check for Frame<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Frame(p, name, _) = self;
+
+        report::scope(o, || p.check(s, o), |r, f| framed(o, name, r, f))
+    }
+}
+
+#[cfg(test)]
+mod tests_label {
+    use std::marker::PhantomData;
+
+    use crate::char;
+    use crate::choice::Or;
+    use crate::context;
+    use crate::delimited_string;
+    use crate::label;
+    use crate::report::parse;
+
+    #[test]
+    fn it_replace_what_is_expected() {
+        let parser = and!(char('{'), label("string literal", delimited_string()));
+        let report = parse(&parser, b"{x").unwrap_err();
+
+        assert_eq!(
+            report.message(),
+            "expected string literal, found 'x' at 1:2"
+        );
+    }
+
+    #[test]
+    fn it_keep_what_is_expected_after_the_start() {
+        let parser = label("pair", and!(char('a'), delimited_string()));
+        let report = parse(&parser, b"ax").unwrap_err();
+
+        assert_eq!(report.message(), "expected '\"', found 'x' at 1:2");
+    }
+
+    #[test]
+    fn it_merge_labelled_alternatives() {
+        let parser = Or(label("a", char('a')), label("b", char('b')), PhantomData);
+        let report = parse(&parser, b"c").unwrap_err();
+
+        assert_eq!(report.message(), "expected a or b, found 'c' at 1:1");
+    }
+
+    #[test]
+    fn it_report_a_context_stack() {
+        let key = context("in object key", label("string literal", delimited_string()));
+        let parser = context("in object", and!(char('{'), and!(rep!(char('\n')), key)));
+        let report = parse(&parser, b"{\n\n1").unwrap_err();
+
+        assert_eq!(
+            report.message(),
+            "in object: in object key: expected '\\n' or string literal, found '1' at 3:1"
+        );
+    }
+
+    #[test]
+    fn it_forget_a_context_once_left() {
+        let parser = and!(context("in key", delimited_string()), char(':'));
+        let report = parse(&parser, b"\"k\"=").unwrap_err();
+
+        assert_eq!(report.message(), "expected ':', found '=' at 1:4");
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Cut parser
+//
+
+// Once a cut is reached a reject is Fatal: no alternative is tried, enclosing ones included, and
+// repetitions stop until the entry point e.g. `and!(char('{'), cut(members))` commits to an object
+// after its opening brace.
+
+pub struct Cut<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn cut<P, A>(p: P) -> Cut<P, A>
+where
+    P: Combine<A>,
+{
+    Cut(p, PhantomData)
+}
+
+impl<P, A> Combine<A> for Cut<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, A> for Cut<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let Cut(p, _) = self;
+
+        match p.parse(s, o) {
+            Reject => Fatal,
+            r => r,
+        }
+    }
+}
+
+impl<P, A> Check for Cut<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Cut(p, _) = self;
+
+        match p.check(s, o) {
+            Reject => Fatal,
+            r => r,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_cut {
+    use std::marker::PhantomData;
+
+    use response::Response::Fatal;
+
+    use crate::char;
+    use crate::choice::Or;
+    use crate::cut;
+    use crate::report::parse;
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_after_a_cut() {
+        let response = and!(char('{'), cut(char('}'))).parse(b"{}", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == ('{', '}') && s == 2, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_backtrack_before_a_cut() {
+        let parser = Or(
+            and!(char('{'), cut(char('}'))),
+            and!(char('['), char(']')),
+            PhantomData,
+        );
+
+        assert_eq!(parser.parse(b"[]", 0).fold(|_, s| s == 2, || false), true);
+        assert_eq!(matches!(parser.parse(b"{]", 0), Fatal), true);
+    }
+
+    #[test]
+    fn it_stop_alternatives_after_a_cut() {
+        let parser = Or(
+            and!(char('{'), cut(char('}'))),
+            and!(char('{'), char(']')),
+            PhantomData,
+        );
+
+        assert_eq!(matches!(parser.parse(b"{]", 0), Fatal), true);
+        assert_eq!(matches!(parser.check(b"{]", 0), Fatal), true);
+    }
+
+    #[test]
+    fn it_stop_enclosing_alternatives_after_a_cut() {
+        let parser = Or(
+            Or(
+                and!(char('{'), cut(char('}'))),
+                and!(char('{'), char(']')),
+                PhantomData,
+            ),
+            and!(char('{'), char('x')),
+            PhantomData,
+        );
+
+        assert_eq!(matches!(parser.parse(b"{x", 0), Fatal), true);
+        assert_eq!(matches!(parser.check(b"{x", 0), Fatal), true);
+    }
+
+    #[test]
+    fn it_stop_a_repetition_after_a_cut() {
+        let response = optrep!(and!(char('a'), cut(char('b')))).parse(b"abac", 0);
+
+        assert_eq!(matches!(response, Fatal), true);
+    }
+
+    #[test]
+    fn it_report_a_failure_after_a_cut() {
+        let parser = Or(
+            and!(char('{'), cut(char('}'))),
+            and!(char('{'), char(']')),
+            PhantomData,
+        );
+        let report = parse(&parser, b"{x").unwrap_err();
+
+        assert_eq!(report.message(), "expected '}', found 'x' at 1:2");
+    }
 }
 
 //  ------------------------------------------------------------------------------------------------
@@ -332,14 +886,30 @@ pub struct Delimited;
 
 impl Combine<(&[u8], usize, usize)> for Delimited {}
 
+// The closing quote is found by a vectorized scan instead of a closure call per character.
+
 impl<'a> Parse<'a, (&'a [u8], usize, usize)> for Delimited {
     fn parse(&self, s: &'a [u8], o: usize) -> Response<(&'a [u8], usize, usize)> {
-        let sep = '"';
-        let response = and!(char(sep), and!(optrep!(not(sep)), char(sep))).check(s, o);
+        let sep = b'"';
+
+        match s.get(o) {
+            Some(c) if *c == sep => match scan::find(&s[o + 1..], sep) {
+                Some(n) => Success((s, o + 1, o + 1 + n), o + n + 2),
+                None => report::track(s.len(), &["'\"'"], stream::end_of_input(1)),
+            },
+            Some(_) => report::reject(o, &["'\"'"]),
+            None => report::track(o, &["'\"'"], stream::end_of_input(1)),
+        }
+    }
+}
 
-        match response {
-            Success(_, no) => Success((s, o + 1, no - 1), no),
+impl Check for Delimited {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        match self.parse(s, o) {
+            Success(_, e) => Success((), e),
             Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
         }
     }
 }
@@ -367,3 +937,103 @@ mod tests_delimited_string {
         assert_eq!(response.fold(|(_, s, e), _| (e - s) == 0, || false), true);
     }
 }
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Thread safety i.e. combinators are Send + Sync when their closures are
+//
+// Pratt parsers erase their operators without Send + Sync bounds, and memo and rule parsers share
+// a Context made of Cells: they are built and used on a single thread.
+
+#[cfg(test)]
+mod tests_send_sync {
+    use std::marker::PhantomData;
+
+    use crate::alt;
+    use crate::any;
+    use crate::char;
+    use crate::choice;
+    use crate::choice::Or;
+    use crate::cut;
+    use crate::delimited_string;
+    use crate::map;
+    use crate::not;
+    use crate::seq;
+    use crate::Frame;
+    use crate::Label;
+    use crate::Opt;
+    use crate::Skip;
+
+    // Compiling is the assertion
+
+    fn send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn it_share_a_satisfy_parser() {
+        send_sync(&any());
+        send_sync(&char('a'));
+        send_sync(&not('a'));
+    }
+
+    #[test]
+    fn it_share_composed_parsers() {
+        send_sync(&and!(char('a'), optrep!(not('b'))));
+        send_sync(&rep!(char('a')));
+        send_sync(&map(char('a'), |c| c.to_ascii_uppercase()));
+        send_sync(&Opt(char('a'), PhantomData));
+        send_sync(&Skip(char('a'), char('b'), PhantomData, PhantomData));
+        send_sync(&Label(char('a'), "a", PhantomData));
+        send_sync(&Frame(char('a'), "a", PhantomData));
+        send_sync(&cut(char('a')));
+        send_sync(&Or(char('a'), char('b'), PhantomData));
+        send_sync(&choice!(char('a'), char('b'), char('c')));
+        send_sync(&seq!(char('a'), char('b')));
+        send_sync(&alt!(char('a'), char('b')));
+        send_sync(&delimited_string());
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Test fixtures shared by the extensions
+//
+
+#[cfg(test)]
+mod tests_fixtures {
+    use crate::map;
+    use crate::Check;
+    use crate::Combine;
+    use crate::Parse;
+    use crate::Satisfy;
+
+    pub fn digit() -> impl for<'a> Parse<'a, i64> + Check + Combine<i64> {
+        map(Satisfy(|c: char| c.is_ascii_digit()), |c| {
+            c as i64 - '0' as i64
+        })
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Extensions
+//
+
+pub mod boxed;
+pub mod chain;
+pub mod choice;
+pub mod derive;
+pub mod ext;
+pub mod grammar;
+pub mod indent;
+pub mod iter;
+pub mod memo;
+pub mod ops;
+pub mod pratt;
+pub mod reader;
+pub mod report;
+pub mod rule;
+pub mod scan;
+pub mod span;
+pub mod stream;
+pub mod string;
+pub mod tuple;
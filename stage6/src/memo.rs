@@ -0,0 +1,397 @@
+//
+// Packrat parsing i.e. each memoized parser is evaluated at most once per offset
+//
+// Each memoized parser owns a table of its responses so any output type can be stored, borrowed
+// ones included. The context identifies the input being parsed: tables are dropped when another
+// input (a slice with another address or length) is given or after a reset. A buffer modified in
+// place keeps its identity so the context must then be reset. An Incomplete response depends on
+// the input received so far and is never kept.
+//
+
+use response::Response::Incomplete;
+
+use crate::choice::{First, FirstSet};
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::collections::HashMap;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The parse context i.e. the identity of the input and the state of the memoization tables
+//
+
+pub struct Context {
+    identifiers: Cell<usize>,
+    input: Cell<(usize, usize)>,
+    generation: Cell<u64>,
+    clock: Cell<u64>,
+    evictions: RefCell<HashMap<(usize, usize), u64>>,
+    growths: RefCell<Vec<Growth>>,
+}
+
+// A left recursive rule growing its seed at a given offset. Entries stored at the same
+// offset meanwhile depend on the current seed and are evicted before each iteration.
+
+struct Growth {
+    head: usize,
+    offset: usize,
+    recursive: bool,
+    involved: Vec<usize>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Context {
+            identifiers: Cell::new(0),
+            input: Cell::new((0, 0)),
+            generation: Cell::new(0),
+            clock: Cell::new(0),
+            evictions: RefCell::new(HashMap::new()),
+            growths: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Entries are only relevant for a given input; this is only required when a buffer is
+    // reused with another content.
+    pub fn reset(&self) {
+        self.generation.set(self.generation.get() + 1);
+        self.evictions.borrow_mut().clear();
+        self.growths.borrow_mut().clear();
+    }
+
+    pub(crate) fn identifier(&self) -> usize {
+        let id = self.identifiers.get();
+        self.identifiers.set(id + 1);
+        id
+    }
+
+    // Called by each memoized parser before a lookup
+
+    pub(crate) fn enter(&self, s: &[u8]) {
+        let input = (s.as_ptr() as usize, s.len());
+
+        if self.input.get() != input {
+            self.input.set(input);
+            self.reset();
+        }
+    }
+
+    fn stamp(&self, id: usize, o: usize) -> u64 {
+        let stamp = self.clock.get() + 1;
+        self.clock.set(stamp);
+
+        for growth in self.growths.borrow_mut().iter_mut() {
+            if growth.offset == o && growth.head != id {
+                growth.involved.push(id);
+            }
+        }
+
+        stamp
+    }
+
+    fn is_valid(&self, id: usize, o: usize, stamp: u64) -> bool {
+        match self.evictions.borrow().get(&(id, o)) {
+            Some(evicted) => stamp > *evicted,
+            None => true,
+        }
+    }
+
+    pub(crate) fn start_growth(&self, head: usize, offset: usize) {
+        self.growths.borrow_mut().push(Growth {
+            head,
+            offset,
+            recursive: false,
+            involved: Vec::new(),
+        })
+    }
+
+    pub(crate) fn detect_recursion(&self, head: usize, offset: usize) {
+        for growth in self.growths.borrow_mut().iter_mut() {
+            if growth.offset == offset && growth.head == head {
+                growth.recursive = true;
+            }
+        }
+    }
+
+    pub(crate) fn is_recursive(&self) -> bool {
+        self.growths.borrow().last().is_some_and(|g| g.recursive)
+    }
+
+    pub(crate) fn evict_involved(&self) {
+        let mut growths = self.growths.borrow_mut();
+
+        if let Some(growth) = growths.last_mut() {
+            let mut evictions = self.evictions.borrow_mut();
+
+            for id in growth.involved.drain(..) {
+                evictions.insert((id, growth.offset), self.clock.get());
+            }
+        }
+    }
+
+    pub(crate) fn stop_growth(&self) {
+        self.growths.borrow_mut().pop();
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The memoization table of a parser
+//
+
+pub(crate) struct Table<A> {
+    id: usize,
+    generation: Cell<u64>,
+    entries: RefCell<HashMap<usize, (u64, Response<A>)>>,
+}
+
+impl<A> Table<A> {
+    pub(crate) fn new(context: &Context) -> Self {
+        Table {
+            id: context.identifier(),
+            generation: Cell::new(context.generation.get()),
+            entries: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    // Entries of a previous input are dropped on first access
+
+    fn entries(&self, context: &Context) -> RefMut<'_, HashMap<usize, (u64, Response<A>)>> {
+        let mut entries = self.entries.borrow_mut();
+
+        if self.generation.get() != context.generation.get() {
+            self.generation.set(context.generation.get());
+            entries.clear();
+        }
+
+        entries
+    }
+
+    pub(crate) fn lookup(&self, context: &Context, o: usize) -> Option<Response<A>>
+    where
+        A: Clone,
+    {
+        match self.entries(context).get(&o) {
+            Some((stamp, r)) if context.is_valid(self.id, o, *stamp) => Some(r.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn store(&self, context: &Context, o: usize, r: Response<A>) {
+        let stamp = context.stamp(self.id, o);
+
+        self.entries(context).insert(o, (stamp, r));
+    }
+
+    pub(crate) fn forget(&self, context: &Context, o: usize) {
+        self.entries(context).remove(&o);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Memo parser
+//
+
+pub struct Memo<'c, P, A>
+where
+    P: Combine<A>,
+{
+    parses: Table<A>,
+    checks: Table<()>,
+    context: &'c Context,
+    parser: P,
+}
+
+pub fn memo<'c, P, A>(context: &'c Context, parser: P) -> Memo<'c, P, A>
+where
+    P: Combine<A>,
+{
+    Memo {
+        parses: Table::new(context),
+        checks: Table::new(context),
+        context,
+        parser,
+    }
+}
+
+impl<'c, P, A> Combine<A> for Memo<'c, P, A> where P: Combine<A> {}
+
+impl<'a, 'c, P, A> Parse<'a, A> for Memo<'c, P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+    A: Clone,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        self.context.enter(s);
+
+        match self.parses.lookup(self.context, o) {
+            Some(r) => r,
+            None => {
+                let r = self.parser.parse(s, o);
+                if !matches!(r, Incomplete(_)) {
+                    self.parses.store(self.context, o, r.clone());
+                }
+                r
+            }
+        }
+    }
+}
+
+impl<'c, P, A> Check for Memo<'c, P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        self.context.enter(s);
+
+        match self.checks.lookup(self.context, o) {
+            Some(r) => r,
+            None => {
+                let r = self.parser.check(s, o);
+                if !matches!(r, Incomplete(_)) {
+                    self.checks.store(self.context, o, r.clone());
+                }
+                r
+            }
+        }
+    }
+}
+
+impl<P, A> First for Memo<'_, P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        self.parser.first()
+    }
+}
+
+#[cfg(test)]
+mod tests_memo {
+    use std::cell::Cell;
+
+    use response::Response::{Incomplete, Reject};
+
+    use crate::char;
+    use crate::memo::{memo, Context};
+    use crate::scan::take_while;
+    use crate::stream::partial;
+    use crate::Check;
+    use crate::Parse;
+    use crate::Satisfy;
+
+    #[test]
+    fn it_parse_a_memoized_character() {
+        let context = Context::new();
+        let response = memo(&context, char('a')).parse(b"a", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'a', || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_memoized_character() {
+        let context = Context::new();
+        let response = memo(&context, char('a')).parse(b"b", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_evaluate_a_parser_once_per_offset() {
+        let calls = Cell::new(0);
+        let context = Context::new();
+        let parser = memo(
+            &context,
+            Satisfy(|c| {
+                calls.set(calls.get() + 1);
+                c == 'a'
+            }),
+        );
+
+        parser.parse(b"a", 0);
+        parser.parse(b"a", 0);
+        parser.check(b"a", 0);
+        parser.check(b"a", 0);
+
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn it_memoize_a_borrowed_output() {
+        let context = Context::new();
+        let parser = memo(&context, take_while(|b| b.is_ascii_digit()));
+        let input = String::from("123;").into_bytes();
+
+        parser.parse(&input, 0);
+        let response = parser.parse(&input, 0);
+
+        assert_eq!(response.fold(|v, _| v == b"123", || false), true);
+    }
+
+    #[test]
+    fn it_evaluate_a_parser_again_on_another_input() {
+        let calls = Cell::new(0);
+        let context = Context::new();
+        let parser = memo(
+            &context,
+            Satisfy(|c| {
+                calls.set(calls.get() + 1);
+                c == 'a'
+            }),
+        );
+
+        parser.parse(b"a", 0);
+        let response = parser.parse(b"b", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn it_evaluate_a_parser_again_after_a_reset() {
+        let calls = Cell::new(0);
+        let context = Context::new();
+        let parser = memo(
+            &context,
+            Satisfy(|c| {
+                calls.set(calls.get() + 1);
+                c == 'a'
+            }),
+        );
+
+        parser.parse(b"a", 0);
+        context.reset();
+        parser.parse(b"a", 0);
+
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn it_do_not_keep_an_incomplete_response() {
+        let context = Context::new();
+        let parser = memo(&context, char('a'));
+        let input = b"";
+
+        let incomplete = partial(|| parser.parse(input, 0));
+        let response = parser.parse(input, 0);
+
+        assert_eq!(matches!(incomplete, Incomplete(1)), true);
+        assert_eq!(matches!(response, Reject), true);
+    }
+}
@@ -0,0 +1,222 @@
+//
+// Operator overloading i.e. `a & b`, `a | b`, `a >> f` and `!a`
+//
+
+use std::marker::PhantomData;
+use std::ops::{BitAnd, BitOr, Not, Shr};
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet, Or};
+use crate::And;
+use crate::Check;
+use crate::Combine;
+use crate::Map;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Parser wrapper
+//
+
+// Operators cannot be implemented on foreign types like closures or on generic parameters so
+// parsers are wrapped. The output type is kept in the wrapper: it drives operator selection.
+
+pub struct Parser<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn parser<P, A>(p: P) -> Parser<P, A>
+where
+    P: Combine<A>,
+{
+    Parser(p, PhantomData)
+}
+
+impl<P, A> Combine<A> for Parser<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, A> for Parser<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        let Parser(p, _) = self;
+
+        p.parse(s, o)
+    }
+}
+
+impl<P, A> Check for Parser<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Parser(p, _) = self;
+
+        p.check(s, o)
+    }
+}
+
+impl<P, A> First for Parser<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Parser(p, _) = self;
+
+        p.first()
+    }
+}
+
+impl<L, R, A, B> BitAnd<Parser<R, B>> for Parser<L, A>
+where
+    L: Combine<A>,
+    R: Combine<B>,
+{
+    type Output = Parser<And<L, R, A, B>, (A, B)>;
+
+    fn bitand(self, r: Parser<R, B>) -> Self::Output {
+        parser(And(self.0, r.0, PhantomData, PhantomData))
+    }
+}
+
+impl<L, R, A> BitOr<Parser<R, A>> for Parser<L, A>
+where
+    L: Combine<A>,
+    R: Combine<A>,
+{
+    type Output = Parser<Or<L, R, A>, A>;
+
+    fn bitor(self, r: Parser<R, A>) -> Self::Output {
+        parser(Or(self.0, r.0, PhantomData))
+    }
+}
+
+impl<P, F, A, B> Shr<F> for Parser<P, A>
+where
+    P: Combine<A>,
+    F: Fn(A) -> B,
+{
+    type Output = Parser<Map<P, F, A>, B>;
+
+    fn shr(self, f: F) -> Self::Output {
+        parser(Map(self.0, f, PhantomData))
+    }
+}
+
+impl<P, A> Not for Parser<P, A>
+where
+    P: Combine<A>,
+{
+    type Output = Parser<NotFollowedBy<P, A>, ()>;
+
+    fn not(self) -> Self::Output {
+        parser(NotFollowedBy(self.0, PhantomData))
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The NotFollowedBy parser i.e. a negative lookahead
+//
+
+pub struct NotFollowedBy<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+impl<P, A> Combine<()> for NotFollowedBy<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, ()> for NotFollowedBy<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<()> {
+        self.check(s, o)
+    }
+}
+
+impl<P, A> Check for NotFollowedBy<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let NotFollowedBy(p, _) = self;
+
+        match p.check(s, o) {
+            Success(_, _) => Reject,
+            Reject => Success((), o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+// Nothing is consumed
+
+impl<P, A> First for NotFollowedBy<P, A>
+where
+    P: Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let mut set = FirstSet::empty();
+        set.nullable = true;
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests_ops {
+    use crate::any;
+    use crate::char;
+    use crate::ops::parser;
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_sequence() {
+        let response = (parser(char('a')) & parser(char('b'))).parse(b"ab", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('a', 'b'), || false), true);
+    }
+
+    #[test]
+    fn it_parse_an_alternative() {
+        let response = (parser(char('a')) | parser(char('b'))).parse(b"b", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'b', || false), true);
+    }
+
+    #[test]
+    fn it_parse_and_map_a_character() {
+        let response = (parser(char('a')) >> |c: char| c.to_ascii_uppercase()).parse(b"a", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'A', || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_negative_lookahead() {
+        let response = (!parser(char('a')) & parser(any())).parse(b"b", 0);
+
+        assert_eq!(
+            response.fold(|v, s| v == ((), 'b') && s == 1, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_cannot_parse_a_negative_lookahead() {
+        let response = (!parser(char('a')) & parser(any())).check(b"a", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_parse_a_composed_expression() {
+        let sign = parser(char('+')) | parser(char('-'));
+        let digit = parser(char('1')) | parser(char('2'));
+        let response = ((sign & digit) >> |(s, d)| format!("{}{}", s, d)).parse(b"-2", 0);
+
+        assert_eq!(response.fold(|v, _| v == "-2", || false), true);
+    }
+}
@@ -0,0 +1,331 @@
+//
+// Operator precedence i.e. Pratt parsing driven by a table of operators
+//
+
+use std::marker::PhantomData;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::stream;
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Pratt parser
+//
+
+#[derive(Clone, Copy)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+// Operators are erased without Send + Sync bounds so a Pratt parser stays on its thread
+
+struct Unary<'o, A>(Box<dyn Check + 'o>, u32, Box<dyn Fn(A) -> A + 'o>);
+
+struct Binary<'o, A>(Box<dyn Check + 'o>, Assoc, u32, Box<dyn Fn(A, A) -> A + 'o>);
+
+pub struct Pratt<'o, P, A>
+where
+    P: Combine<A>,
+{
+    atom: P,
+    prefix: Vec<Unary<'o, A>>,
+    infix: Vec<Binary<'o, A>>,
+    postfix: Vec<Unary<'o, A>>,
+    _a: PhantomData<A>,
+}
+
+pub fn pratt<'o, P, A>(atom: P) -> Pratt<'o, P, A>
+where
+    P: Combine<A>,
+{
+    Pratt {
+        atom,
+        prefix: Vec::new(),
+        infix: Vec::new(),
+        postfix: Vec::new(),
+        _a: PhantomData,
+    }
+}
+
+// Binding powers are doubled so the associativity can be encoded with a +1 on one side.
+
+impl<'o, P, A> Pratt<'o, P, A>
+where
+    P: Combine<A>,
+{
+    pub fn prefix<O, F>(mut self, op: O, power: u32, f: F) -> Self
+    where
+        O: Check + 'o,
+        F: Fn(A) -> A + 'o,
+    {
+        self.prefix.push(Unary(Box::new(op), power, Box::new(f)));
+        self
+    }
+
+    pub fn infix<O, F>(mut self, op: O, assoc: Assoc, power: u32, f: F) -> Self
+    where
+        O: Check + 'o,
+        F: Fn(A, A) -> A + 'o,
+    {
+        self.infix
+            .push(Binary(Box::new(op), assoc, power, Box::new(f)));
+        self
+    }
+
+    pub fn postfix<O, F>(mut self, op: O, power: u32, f: F) -> Self
+    where
+        O: Check + 'o,
+        F: Fn(A) -> A + 'o,
+    {
+        self.postfix.push(Unary(Box::new(op), power, Box::new(f)));
+        self
+    }
+
+    // The operators are alternatives: a cut in one of them stops the whole parse
+
+    fn lookup<'b, I>(operators: I, s: &[u8], o: usize) -> Response<(usize, usize)>
+    where
+        I: Iterator<Item = &'b dyn Check>,
+        'o: 'b,
+    {
+        for (i, op) in operators.enumerate() {
+            match op.check(s, o) {
+                Success(_, e) => return Success((i, e), e),
+                Reject => (),
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Reject
+    }
+
+    fn unary(operators: &[Unary<'o, A>], s: &[u8], o: usize) -> Response<(usize, usize)> {
+        Self::lookup(operators.iter().map(|Unary(op, _, _)| op.as_ref()), s, o)
+    }
+
+    fn binary(operators: &[Binary<'o, A>], s: &[u8], o: usize) -> Response<(usize, usize)> {
+        Self::lookup(
+            operators.iter().map(|Binary(op, _, _, _)| op.as_ref()),
+            s,
+            o,
+        )
+    }
+
+    fn climb<V, T, R>(&self, s: &[u8], o: usize, min: u32, atom: &T, reduce: &R) -> Response<V>
+    where
+        T: Fn(usize) -> Response<V>,
+        R: Fn(Step<V>) -> V,
+    {
+        let (mut lhs, mut o) = match Self::unary(&self.prefix, s, o) {
+            Success((i, e), _) => match self.climb(s, e, self.prefix[i].1 * 2, atom, reduce) {
+                Success(v, e) => (reduce(Step::Prefix(i, v)), e),
+                Reject => return Reject,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            },
+            Reject => match atom(o) {
+                Success(v, e) => (v, e),
+                Reject => return Reject,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            },
+            Incomplete(n) => return Incomplete(n),
+            Fatal => return Fatal,
+        };
+
+        loop {
+            // An operator may still follow once more input is available
+            if o >= s.len() && stream::is_partial() {
+                return Incomplete(1);
+            }
+
+            match Self::unary(&self.postfix, s, o) {
+                Success((i, e), _) => {
+                    if self.postfix[i].1 * 2 < min {
+                        break;
+                    }
+
+                    lhs = reduce(Step::Postfix(i, lhs));
+                    o = e;
+                    continue;
+                }
+                Reject => (),
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+
+            let (i, e) = match Self::binary(&self.infix, s, o) {
+                Success((i, e), _) => (i, e),
+                Reject => break,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            };
+
+            let Binary(_, assoc, power, _) = self.infix[i];
+            let (left, right) = match assoc {
+                Assoc::Left => (power * 2, power * 2 + 1),
+                Assoc::Right => (power * 2 + 1, power * 2),
+            };
+
+            if left < min {
+                break;
+            }
+
+            // When the right operand is missing the operator is not consumed.
+            match self.climb(s, e, right, atom, reduce) {
+                Success(rhs, e) => {
+                    lhs = reduce(Step::Infix(i, lhs, rhs));
+                    o = e;
+                }
+                Reject => break,
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+
+        Success(lhs, o)
+    }
+}
+
+enum Step<V> {
+    Prefix(usize, V),
+    Infix(usize, V, V),
+    Postfix(usize, V),
+}
+
+impl<'o, P, A> Combine<A> for Pratt<'o, P, A> where P: Combine<A> {}
+
+impl<'a, 'o, P, A> Parse<'a, A> for Pratt<'o, P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        self.climb(s, o, 0, &|o| self.atom.parse(s, o), &|step| match step {
+            Step::Prefix(i, v) => (self.prefix[i].2)(v),
+            Step::Infix(i, l, r) => (self.infix[i].3)(l, r),
+            Step::Postfix(i, v) => (self.postfix[i].2)(v),
+        })
+    }
+}
+
+impl<'o, P, A> Check for Pratt<'o, P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        self.climb(s, o, 0, &|o| self.atom.check(s, o), &|_| ())
+    }
+}
+
+// Prefix operators are type erased so their first bytes are unknown
+
+impl<P, A> First for Pratt<'_, P, A>
+where
+    P: Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_pratt {
+    use response::Response::Fatal;
+
+    use crate::char;
+    use crate::cut;
+    use crate::pratt::{pratt, Assoc, Pratt};
+    use crate::tests_fixtures::digit;
+    use crate::Check;
+    use crate::Combine;
+    use crate::Parse;
+
+    fn arithmetic<'o, P>(atom: P) -> Pratt<'o, P, i64>
+    where
+        P: Combine<i64>,
+    {
+        pratt(atom)
+            .prefix(char('-'), 3, |a| -a)
+            .infix(char('+'), Assoc::Left, 1, |a, b| a + b)
+            .infix(char('-'), Assoc::Left, 1, |a, b| a - b)
+            .infix(char('*'), Assoc::Left, 2, |a, b| a * b)
+            .infix(char('^'), Assoc::Right, 4, |a, b| a.pow(b as u32))
+            .postfix(char('!'), 5, |a| (1..=a).product())
+    }
+
+    #[test]
+    fn it_parse_an_atom() {
+        let response = arithmetic(digit()).parse(b"7", 0);
+
+        assert_eq!(response.fold(|v, s| v == 7 && s == 1, || false), true);
+    }
+
+    #[test]
+    fn it_parse_operators_with_precedence() {
+        let response = arithmetic(digit()).parse(b"1+2*3", 0);
+
+        assert_eq!(response.fold(|v, _| v == 7, || false), true);
+    }
+
+    #[test]
+    fn it_parse_left_associative_operators() {
+        let response = arithmetic(digit()).parse(b"9-2-3", 0);
+
+        assert_eq!(response.fold(|v, _| v == 4, || false), true);
+    }
+
+    #[test]
+    fn it_parse_right_associative_operators() {
+        let response = arithmetic(digit()).parse(b"2^3^2", 0);
+
+        assert_eq!(response.fold(|v, _| v == 512, || false), true);
+    }
+
+    #[test]
+    fn it_parse_prefix_and_postfix_operators() {
+        let response = arithmetic(digit()).parse(b"-3!+1", 0);
+
+        assert_eq!(response.fold(|v, _| v == -5, || false), true);
+    }
+
+    #[test]
+    fn it_do_not_consume_a_dangling_operator() {
+        let response = arithmetic(digit()).parse(b"1+2*", 0);
+
+        assert_eq!(response.fold(|v, s| v == 3 && s == 3, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_missing_atom() {
+        let response = arithmetic(digit()).parse(b"*2", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_check_operators_with_precedence() {
+        let response = arithmetic(digit()).check(b"-1+2*3!^2", 0);
+
+        assert_eq!(response.fold(|_, s| s == 9, || false), true);
+    }
+
+    #[test]
+    fn it_stop_looking_up_operators_after_a_cut() {
+        let parser = pratt(digit())
+            .infix(and!(char('.'), cut(char('.'))), Assoc::Left, 1, |a, b| {
+                a + b
+            })
+            .infix(char('.'), Assoc::Left, 1, |a, b| a * b);
+
+        assert_eq!(parser.parse(b"2..3", 0).fold(|v, _| v == 5, || false), true);
+        assert_eq!(matches!(parser.parse(b"2.3", 0), Fatal), true);
+    }
+}
@@ -0,0 +1,192 @@
+//
+// Reader backed inputs i.e. records parsed from a BufRead without loading the whole input
+//
+// Records are separated by a delimiter byte, e.g. `\n` for line oriented formats. Each record is
+// copied into a single buffer reused from one record to the next and parsed in place so values
+// may borrow it like any other `&[u8]` input. The memory used is at most the reader buffer plus
+// the record limit given at construction: a longer record is an error, not a reallocation.
+//
+
+use std::io;
+use std::io::BufRead;
+
+use response::Response::Success;
+
+use crate::Parse;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Reader
+//
+
+pub struct Reader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    delimiter: u8,
+    limit: usize,
+    record: usize,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(reader: R, delimiter: u8, limit: usize) -> Self {
+        Reader {
+            reader,
+            buffer: Vec::with_capacity(limit),
+            delimiter,
+            limit,
+            record: 0,
+        }
+    }
+
+    pub fn lines(reader: R, limit: usize) -> Self {
+        Self::new(reader, b'\n', limit)
+    }
+
+    // The number of records read so far
+
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    // The next record parsed as a whole, None once the reader is exhausted
+
+    pub fn next<'a, P, A>(&'a mut self, p: &P) -> Option<io::Result<A>>
+    where
+        P: Parse<'a, A>,
+    {
+        match self.read() {
+            Ok(true) => (),
+            Ok(false) => return None,
+            Err(e) => return Some(Err(e)),
+        }
+
+        let record = self.record;
+        let buffer = &self.buffer[..];
+
+        match p.parse(buffer, 0) {
+            Success(v, o) if o == buffer.len() => Some(Ok(v)),
+            _ => Some(Err(invalid(format!("unable to parse record {}", record)))),
+        }
+    }
+
+    fn read(&mut self) -> io::Result<bool> {
+        self.buffer.clear();
+
+        let delimiter = self.delimiter;
+        let mut found = false;
+        let mut empty = true;
+        let mut overflow = false;
+
+        while !found {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if available.is_empty() {
+                break;
+            }
+
+            empty = false;
+
+            let (length, consumed) = match available.iter().position(|b| *b == delimiter) {
+                Some(p) => {
+                    found = true;
+                    (p, p + 1)
+                }
+                None => (available.len(), available.len()),
+            };
+
+            // A record too long is skipped so the next one can still be read
+            overflow = overflow || self.buffer.len() + length > self.limit;
+
+            if !overflow {
+                self.buffer.extend_from_slice(&available[..length]);
+            }
+
+            self.reader.consume(consumed);
+        }
+
+        if empty {
+            return Ok(false);
+        }
+
+        self.record += 1;
+
+        if overflow {
+            return Err(invalid(format!(
+                "record {} exceeds {} bytes",
+                self.record, self.limit
+            )));
+        }
+
+        Ok(true)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests_reader {
+    use std::io::BufReader;
+
+    use crate::delimited_string;
+    use crate::reader::Reader;
+
+    fn reader(data: &[u8], limit: usize) -> Reader<BufReader<&[u8]>> {
+        Reader::lines(BufReader::with_capacity(4, data), limit)
+    }
+
+    #[test]
+    fn it_parse_borrowed_records() {
+        let mut reader = reader(b"\"a\"\n\"bcd\"\n\"\"", 8);
+        let mut values = Vec::new();
+
+        while let Some(r) = reader.next(&delimited_string()) {
+            let (s, b, e) = r.unwrap();
+            values.push(String::from_utf8_lossy(&s[b..e]).into_owned());
+        }
+
+        assert_eq!(values, vec!["a", "bcd", ""]);
+        assert_eq!(reader.record(), 3);
+    }
+
+    #[test]
+    fn it_reject_a_record_partially_parsed() {
+        let mut reader = reader(b"\"a\"b\n", 8);
+        let response = reader.next(&delimited_string());
+
+        assert_eq!(response.is_some_and(|r| r.is_err()), true);
+    }
+
+    #[test]
+    fn it_reject_a_record_above_the_limit() {
+        let mut reader = reader(b"\"a\"\n\"abcdefgh\"\n\"b\"", 8);
+
+        assert_eq!(
+            reader.next(&delimited_string()).is_some_and(|r| r.is_ok()),
+            true
+        );
+        assert_eq!(
+            reader.next(&delimited_string()).is_some_and(|r| r.is_err()),
+            true
+        );
+        assert_eq!(
+            reader.next(&delimited_string()).is_some_and(|r| r.is_ok()),
+            true
+        );
+    }
+
+    #[test]
+    fn it_read_nothing() {
+        let mut reader = reader(b"", 8);
+
+        assert_eq!(reader.next(&delimited_string()).is_none(), true);
+    }
+}
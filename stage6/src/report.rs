@@ -0,0 +1,533 @@
+//
+// Error reports i.e. the furthest failure of a parse rendered with a source snippet
+//
+// Rejected primitives record the offset and the items they expected while a report is built.
+// Like the partial mode of streams the recording is a thread local state so the Parse signature
+// does not change; out of `parse` below recording does nothing and expected items are not even
+// described. Memoized responses are not recorded again when they are reused.
+//
+// The failure does not travel in the Response: a Reject carries no data and Response is shared
+// by every stage, so carrying a failure would change each combinator and make each response
+// larger, or add a context to every Parse. The price is a flag read by each rejected primitive;
+// the recording itself is kept out of line. On the reject heavy benches, in ns/iter over three
+// runs each:
+//
+//     naive_backtracking      naive_choice
+//     86,381..112,563         53,585..66,812     recording inlined in each primitive
+//     61,302..66,224          30,716..37,338     recording out of line i.e. as below
+//     22,848..24,652          10,351..11,527     no flag at all (reports disabled)
+//
+// The remaining gap is not the thread local read but the side effect itself: without it a
+// rejected character is pure and alternatives are compiled as a single match.
+//
+// The recording belongs to the thread calling `parse`: parsers run on another thread are not
+// recorded and a parser run from a callback on another input must be run `untracked` (or
+// through a nested `parse`) so its offsets are not mixed with the ones of the reported input.
+//
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::FirstSet;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The furthest failure
+//
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Failure {
+    pub offset: usize,
+    pub expected: Vec<String>,
+    pub context: Vec<&'static str>,
+}
+
+impl Failure {
+    pub fn new(offset: usize) -> Self {
+        Failure {
+            offset,
+            expected: Vec::new(),
+            context: Vec::new(),
+        }
+    }
+
+    // Failures before the furthest one are ignored, failures at the same offset are merged
+
+    fn merge(&mut self, o: usize, expected: &[&str]) {
+        if o < self.offset {
+            return;
+        }
+
+        if o > self.offset {
+            *self = Failure::new(o);
+        }
+
+        for item in expected {
+            if !self.expected.iter().any(|e| e == item) {
+                self.expected.push(item.to_string());
+            }
+        }
+    }
+
+    // Merges the failure of a nested scope
+
+    fn absorb(&mut self, other: Failure) {
+        if other.offset > self.offset {
+            *self = other;
+        } else if other.offset == self.offset {
+            if other.context.len() > self.context.len() {
+                self.context = other.context;
+            }
+
+            for item in other.expected {
+                if !self.expected.contains(&item) {
+                    self.expected.push(item);
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+    static FAILURE: RefCell<Option<Failure>> = const { RefCell::new(None) };
+}
+
+pub fn is_tracking() -> bool {
+    TRACKING.with(|t| t.get())
+}
+
+// The response of a primitive at `o`, recorded when it is a Reject
+
+pub fn track<A>(o: usize, expected: &[&str], r: Response<A>) -> Response<A> {
+    if let (Reject, true) = (&r, is_tracking()) {
+        record(o, expected);
+    }
+
+    r
+}
+
+pub fn reject<A>(o: usize, expected: &[&str]) -> Response<A> {
+    track(o, expected, Reject)
+}
+
+// Same as track with expected items only described while a report is built
+
+pub fn track_with<A, F>(o: usize, expected: F, r: Response<A>) -> Response<A>
+where
+    F: FnOnce() -> Vec<String>,
+{
+    if let (Reject, true) = (&r, is_tracking()) {
+        describe(o, expected);
+    }
+
+    r
+}
+
+pub fn reject_with<A, F>(o: usize, expected: F) -> Response<A>
+where
+    F: FnOnce() -> Vec<String>,
+{
+    track_with(o, expected, Reject)
+}
+
+#[cold]
+fn describe<F>(o: usize, expected: F)
+where
+    F: FnOnce() -> Vec<String>,
+{
+    let expected = expected();
+    let expected: Vec<&str> = expected.iter().map(String::as_str).collect();
+
+    record(o, &expected);
+}
+
+#[cold]
+fn record(o: usize, expected: &[&str]) {
+    FAILURE.with(|f| {
+        if let Some(failure) = f.borrow_mut().as_mut() {
+            failure.merge(o, expected)
+        }
+    });
+}
+
+// The bytes of a FIRST set; a set too large to be read is not described
+
+pub(crate) fn bytes(set: &FirstSet) -> Vec<String> {
+    let bytes: Vec<usize> = (0..256).filter(|b| set.bytes[*b]).collect();
+
+    if bytes.len() > 8 {
+        return Vec::new();
+    }
+
+    bytes
+        .into_iter()
+        .map(|b| format!("'{}'", (b as u8 as char).escape_default()))
+        .collect()
+}
+
+// Restores the previous recording when dropped, even if the parse panics
+
+struct Recording(bool, Option<Failure>);
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        TRACKING.with(|t| t.set(self.0));
+        FAILURE.with(|f| f.replace(self.1.take()));
+    }
+}
+
+// Runs `f` without recording, the current failure is restored afterwards
+
+pub fn untracked<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let _recording = Recording(
+        TRACKING.with(|t| t.replace(false)),
+        FAILURE.with(|f| f.take()),
+    );
+
+    f()
+}
+
+// Runs a parser at `o` with a failure of its own, updated before being merged back. This is how
+// labels and contexts only change what is recorded inside of them.
+
+pub(crate) fn scope<R, F, U>(o: usize, run: F, update: U) -> R
+where
+    F: FnOnce() -> R,
+    U: FnOnce(&R, &mut Failure),
+{
+    if !is_tracking() {
+        return run();
+    }
+
+    let outer = FAILURE.with(|f| f.replace(Some(Failure::new(o))));
+    let result = run();
+    let mut inner = FAILURE
+        .with(|f| f.replace(outer))
+        .unwrap_or(Failure::new(o));
+
+    update(&result, &mut inner);
+
+    FAILURE.with(|f| {
+        if let Some(failure) = f.borrow_mut().as_mut() {
+            failure.absorb(inner)
+        }
+    });
+
+    result
+}
+
+// Parses a complete input, the furthest failure is reported when the parser rejects it
+
+pub fn parse<'a, P, A>(p: &P, s: &'a [u8]) -> Result<(A, usize), Report<'a>>
+where
+    P: Parse<'a, A>,
+{
+    let recording = Recording(
+        TRACKING.with(|t| t.replace(true)),
+        FAILURE.with(|f| f.replace(Some(Failure::new(0)))),
+    );
+    let response = p.parse(s, 0);
+    let failure = FAILURE.with(|f| f.take()).unwrap_or(Failure::new(0));
+
+    drop(recording);
+
+    match response {
+        Success(v, o) => Ok((v, o)),
+        Reject | Fatal => Err(Report::new(failure, s)),
+        Incomplete(_) => Err(Report::new(Failure::new(s.len()), s)),
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Report
+//
+
+pub struct Report<'a> {
+    pub failure: Failure,
+    pub source: &'a [u8],
+}
+
+struct Style {
+    error: &'static str,
+    message: &'static str,
+    gutter: &'static str,
+    caret: &'static str,
+    reset: &'static str,
+}
+
+const PLAIN: Style = Style {
+    error: "",
+    message: "",
+    gutter: "",
+    caret: "",
+    reset: "",
+};
+
+const ANSI: Style = Style {
+    error: "\x1b[1;31m",
+    message: "\x1b[1m",
+    gutter: "\x1b[1;34m",
+    caret: "\x1b[1;31m",
+    reset: "\x1b[0m",
+};
+
+impl<'a> Report<'a> {
+    pub fn new(failure: Failure, source: &'a [u8]) -> Self {
+        Report { failure, source }
+    }
+
+    // One based line and column, the column counts characters
+
+    pub fn position(&self) -> (usize, usize) {
+        let (start, _) = self.line();
+        let line = 1 + self.source[..start].iter().filter(|c| **c == b'\n').count();
+        let column = 1 + String::from_utf8_lossy(&self.source[start..self.offset()])
+            .chars()
+            .count();
+
+        (line, column)
+    }
+
+    // e.g. "in object key: expected string literal at 3:14"
+
+    pub fn message(&self) -> String {
+        let (line, column) = self.position();
+
+        format!("{} at {}:{}", self.description(), line, column)
+    }
+
+    pub fn render(&self) -> String {
+        self.styled(&PLAIN)
+    }
+
+    pub fn render_ansi(&self) -> String {
+        self.styled(&ANSI)
+    }
+
+    fn offset(&self) -> usize {
+        self.failure.offset.min(self.source.len())
+    }
+
+    // The bounds of the line containing the failure, without its end of line
+
+    fn line(&self) -> (usize, usize) {
+        let offset = self.offset();
+        let start = self.source[..offset]
+            .iter()
+            .rposition(|c| *c == b'\n')
+            .map_or(0, |p| p + 1);
+        let end = self.source[offset..]
+            .iter()
+            .position(|c| *c == b'\n')
+            .map_or(self.source.len(), |p| offset + p);
+
+        (start, end)
+    }
+
+    fn found(&self) -> String {
+        match self.source.get(self.offset()) {
+            Some(c) => format!("'{}'", (*c as char).escape_default()),
+            None => String::from("end of input"),
+        }
+    }
+
+    fn description(&self) -> String {
+        let found = self.found();
+        let expected = &self.failure.expected;
+
+        let description = match expected.split_last() {
+            None => format!("unexpected {}", found),
+            Some((last, [])) => format!("expected {}, found {}", last, found),
+            Some((last, others)) => {
+                format!(
+                    "expected {} or {}, found {}",
+                    others.join(", "),
+                    last,
+                    found
+                )
+            }
+        };
+
+        self.failure
+            .context
+            .iter()
+            .rev()
+            .fold(description, |d, frame| format!("{}: {}", frame, d))
+    }
+
+    fn styled(&self, style: &Style) -> String {
+        let (line, column) = self.position();
+        let (start, end) = self.line();
+        let number = line.to_string();
+        let margin = " ".repeat(number.len());
+
+        format!(
+            "{}error{}{}: {}{}\n\
+             {}{}-->{} {}:{}\n\
+             {}{} |{}\n\
+             {}{} |{} {}\n\
+             {}{} |{} {}{}^{}\n",
+            style.error,
+            style.reset,
+            style.message,
+            self.description(),
+            style.reset,
+            margin,
+            style.gutter,
+            style.reset,
+            line,
+            column,
+            style.gutter,
+            margin,
+            style.reset,
+            style.gutter,
+            number,
+            style.reset,
+            String::from_utf8_lossy(&self.source[start..end]),
+            style.gutter,
+            margin,
+            style.reset,
+            " ".repeat(column - 1),
+            style.caret,
+            style.reset,
+        )
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+impl fmt::Debug for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+#[cfg(test)]
+mod tests_report {
+    use crate::any;
+    use crate::char;
+    use crate::choice;
+    use crate::delimited_string;
+    use crate::map;
+    use crate::report::{is_tracking, parse, untracked, Failure, Report};
+    use crate::Parse;
+
+    #[test]
+    fn it_report_the_furthest_failure() {
+        let parser = and!(char('{'), and!(rep!(char(' ')), delimited_string()));
+        let report = parse(&parser, b"{  x}").unwrap_err();
+
+        assert_eq!(report.failure.offset, 3);
+        assert_eq!(report.message(), "expected ' ' or '\"', found 'x' at 1:4");
+    }
+
+    #[test]
+    fn it_report_an_unexpected_end_of_input() {
+        let report = parse(&and!(char('a'), char('b')), b"a").unwrap_err();
+
+        assert_eq!(report.message(), "expected 'b', found end of input at 1:2");
+    }
+
+    #[test]
+    fn it_report_an_undescribed_end_of_input() {
+        let report = parse(&and!(char('a'), any()), b"a").unwrap_err();
+
+        assert_eq!(report.message(), "unexpected end of input at 1:2");
+    }
+
+    #[test]
+    fn it_report_the_first_bytes_of_a_choice() {
+        let parser = and!(char('a'), choice!(char('b'), char('c')));
+        let report = parse(&parser, b"ax").unwrap_err();
+
+        assert_eq!(report.message(), "expected 'b' or 'c', found 'x' at 1:2");
+    }
+
+    // The callback parses another input whose furthest failure is at offset 9
+
+    fn other(c: char) -> char {
+        let response = and!(rep!(char('x')), char('y')).parse(b"xxxxxxxxxz", 0);
+
+        response.fold(|_, _| c, || c)
+    }
+
+    #[test]
+    fn it_ignore_an_untracked_parse() {
+        let parser = and!(map(char('a'), |c| untracked(|| other(c))), char('c'));
+        let report = parse(&parser, b"ab").unwrap_err();
+
+        assert_eq!(report.message(), "expected 'c', found 'b' at 1:2");
+    }
+
+    #[test]
+    fn it_keep_the_failure_of_a_nested_report() {
+        let nested = |c| parse(&char('x'), b"y").map_or(c, |_| c);
+        let parser = and!(map(char('a'), nested), char('c'));
+        let report = parse(&parser, b"ab").unwrap_err();
+
+        assert_eq!(report.message(), "expected 'c', found 'b' at 1:2");
+        assert_eq!(is_tracking(), false);
+    }
+
+    #[test]
+    fn it_do_not_track_out_of_a_report() {
+        let response = char('a').parse(b"b", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+        assert_eq!(is_tracking(), false);
+    }
+
+    #[test]
+    fn it_parse_without_report() {
+        let response = parse(&char('a'), b"a");
+
+        assert_eq!(response.map(|(v, o)| v == 'a' && o == 1).unwrap(), true);
+    }
+
+    #[test]
+    fn it_render_a_snippet() {
+        let failure = Failure {
+            offset: 11,
+            expected: vec![
+                String::from("'a'"),
+                String::from("'b'"),
+                String::from("'c'"),
+            ],
+            context: vec!["in object", "in key"],
+        };
+        let report = Report::new(failure, b"{\n  \"k\": 1,\n  ]\n}");
+
+        assert_eq!(
+            report.render(),
+            "error: in object: in key: expected 'a', 'b' or 'c', found '\\n'\n \
+             --> 2:10\n  \
+             |\n\
+             2 |   \"k\": 1,\n  \
+             |          ^\n"
+        );
+    }
+
+    #[test]
+    fn it_render_a_colored_snippet() {
+        let report = Report::new(Failure::new(1), b"ab");
+
+        assert_eq!(report.render_ansi().contains("\x1b[1;31m^\x1b[0m"), true);
+        assert_eq!(
+            report.render_ansi().starts_with("\x1b[1;31merror\x1b[0m"),
+            true
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//
+// Recursive rules with left recursion support i.e. seed growing (Warth et al.)
+//
+
+use std::cell::Cell;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::memo::{Context, Table};
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Rule parser
+//
+
+pub trait Body<'a, A>: Parse<'a, A> + Check {}
+
+impl<'a, A, P> Body<'a, A> for P where P: Parse<'a, A> + Check {}
+
+pub struct Rule<'a, 'r, A> {
+    parses: Table<A>,
+    checks: Table<()>,
+    context: &'r Context,
+    body: Cell<Option<&'r dyn Body<'a, A>>>,
+}
+
+pub fn rule<'a, 'r, A>(context: &'r Context) -> Rule<'a, 'r, A> {
+    Rule {
+        parses: Table::new(context),
+        checks: Table::new(context),
+        context,
+        body: Cell::new(None),
+    }
+}
+
+impl<'a, 'r, A> Rule<'a, 'r, A> {
+    // A rule is declared first and defined later so its body can refer to the rule itself.
+    pub fn define(&self, body: &'r dyn Body<'a, A>) {
+        self.body.set(Some(body))
+    }
+
+    fn body(&self) -> &'r dyn Body<'a, A> {
+        self.body.get().expect("rule used before its definition")
+    }
+
+    fn grow<B, F>(&self, table: &Table<B>, s: &[u8], o: usize, eval: F) -> Response<B>
+    where
+        B: Clone,
+        F: Fn() -> Response<B>,
+    {
+        self.context.enter(s);
+
+        if let Some(r) = table.lookup(self.context, o) {
+            self.context.detect_recursion(table.id(), o);
+            return r;
+        }
+
+        // The seed is a failure: a left recursive call is rejected during the first iteration.
+        table.store(self.context, o, Reject);
+        self.context.start_growth(table.id(), o);
+
+        let mut end = None;
+        let mut incomplete = None;
+
+        loop {
+            self.context.evict_involved();
+
+            match eval() {
+                Success(v, e) if end.map_or(true, |end| e > end) => {
+                    end = Some(e);
+                    table.store(self.context, o, Success(v, e));
+                }
+                Incomplete(n) if end.is_none() => {
+                    incomplete = Some(n);
+                    break;
+                }
+                Fatal if end.is_none() => {
+                    table.store(self.context, o, Fatal);
+                    break;
+                }
+                _ => break,
+            }
+
+            if !self.context.is_recursive() {
+                break;
+            }
+        }
+
+        self.context.stop_growth();
+
+        // The seed is dropped: an Incomplete response is not kept
+        if let Some(n) = incomplete {
+            table.forget(self.context, o);
+            return Incomplete(n);
+        }
+
+        table.lookup(self.context, o).unwrap_or(Reject)
+    }
+}
+
+impl<'a, 'r, A> Combine<A> for Rule<'a, 'r, A> {}
+
+impl<'a, 'r, A> Parse<'a, A> for Rule<'a, 'r, A>
+where
+    A: Clone,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+        self.grow(&self.parses, s, o, || self.body().parse(s, o))
+    }
+}
+
+impl<'a, 'r, A> Check for Rule<'a, 'r, A> {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        self.grow(&self.checks, s, o, || self.body().check(s, o))
+    }
+}
+
+// The body may be recursive and is defined after the rule
+
+impl<A> First for Rule<'_, '_, A> {
+    fn first(&self) -> FirstSet {
+        FirstSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests_rule {
+    use response::Response::{Incomplete, Reject};
+
+    use crate::char;
+    use crate::map;
+    use crate::memo::Context;
+    use crate::or;
+    use crate::rule::rule;
+    use crate::scan::take_while;
+    use crate::stream::partial;
+    use crate::tests_fixtures::digit;
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_non_recursive_rule() {
+        let context = Context::new();
+        let number = digit();
+        let value = rule(&context);
+        value.define(&number);
+
+        let response = value.parse(b"7", 0);
+
+        assert_eq!(response.fold(|v, s| v == 7 && s == 1, || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_directly_left_recursive_rule() {
+        let context = Context::new();
+        let number = digit();
+        let expr = rule(&context);
+        let body = or!(
+            map(and!(and!(&expr, char('-')), &number), |((a, _), b)| a - b),
+            &number
+        );
+        expr.define(&body);
+
+        let response = expr.parse(b"7-2-1", 0);
+
+        assert_eq!(response.fold(|v, s| v == 4 && s == 5, || false), true);
+    }
+
+    #[test]
+    fn it_parse_an_indirectly_left_recursive_rule() {
+        let context = Context::new();
+        let number = digit();
+        let expr = rule(&context);
+        let difference = rule(&context);
+        let body = or!(
+            map(and!(and!(&expr, char('-')), &number), |((a, _), b)| a - b),
+            &number
+        );
+        expr.define(&difference);
+        difference.define(&body);
+
+        let response = expr.parse(b"7-2-1", 0);
+
+        assert_eq!(response.fold(|v, s| v == 4 && s == 5, || false), true);
+    }
+
+    #[test]
+    fn it_parse_nested_left_recursive_rules() {
+        let context = Context::new();
+        let number = digit();
+        let expr = rule(&context);
+        let term = rule(&context);
+        let expr_body = or!(
+            map(and!(and!(&expr, char('+')), &term), |((a, _), b)| a + b),
+            &term
+        );
+        let term_body = or!(
+            map(and!(and!(&term, char('*')), &number), |((a, _), b)| a * b),
+            &number
+        );
+        expr.define(&expr_body);
+        term.define(&term_body);
+
+        let response = expr.parse(b"2+3*4+1", 0);
+
+        assert_eq!(response.fold(|v, s| v == 15 && s == 7, || false), true);
+    }
+
+    #[test]
+    fn it_check_a_directly_left_recursive_rule() {
+        let context = Context::new();
+        let number = digit();
+        let expr = rule(&context);
+        let body = or!(
+            map(and!(and!(&expr, char('-')), &number), |((a, _), b)| a - b),
+            &number
+        );
+        expr.define(&body);
+
+        let response = expr.check(b"7-2-1", 0);
+
+        assert_eq!(response.fold(|_, s| s == 5, || false), true);
+    }
+
+    #[test]
+    fn it_parse_a_left_recursive_rule_with_a_borrowed_output() {
+        let context = Context::new();
+        let digits = take_while(|b| b.is_ascii_digit());
+        let last = rule(&context);
+        let body = or!(
+            map(and!(and!(&last, char(',')), &digits), |(_, v)| v),
+            &digits
+        );
+        last.define(&body);
+
+        let input = String::from("1,22,3;").into_bytes();
+        let response = last.parse(&input, 0);
+
+        assert_eq!(response.fold(|v, s| v == b"3" && s == 6, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_left_recursive_rule() {
+        let context = Context::new();
+        let number = digit();
+        let expr = rule(&context);
+        let body = or!(
+            map(and!(and!(&expr, char('-')), &number), |((a, _), b)| a - b),
+            &number
+        );
+        expr.define(&body);
+
+        let response = expr.parse(b"-1", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_do_not_keep_an_incomplete_rule() {
+        let context = Context::new();
+        let number = digit();
+        let value = rule(&context);
+        value.define(&number);
+        let input = b"";
+
+        let incomplete = partial(|| value.parse(input, 0));
+        let response = value.parse(input, 0);
+
+        assert_eq!(matches!(incomplete, Incomplete(1)), true);
+        assert_eq!(matches!(response, Reject), true);
+    }
+}
@@ -0,0 +1,318 @@
+//
+// Scanning parsers i.e. runs of bytes recognized without a closure call per byte
+//
+// `take_until` looks for a byte 16 bytes at a time with SSE2 on x86_64 (always available on this
+// architecture) and 8 bytes at a time with a word based (SWAR) scan elsewhere. `take_while` is
+// not vectorized: it looks each byte up in a 256 entries class table.
+//
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::report;
+use crate::stream;
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Byte search
+//
+
+pub fn find(s: &[u8], byte: u8) -> Option<usize> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        sse2::find(s, byte)
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        swar::find(s, byte)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use std::arch::x86_64::{
+        __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi8,
+    };
+
+    pub fn find(s: &[u8], byte: u8) -> Option<usize> {
+        let mut offset = 0;
+
+        // SAFETY: SSE2 is part of the x86_64 baseline and each unaligned load reads 16 bytes
+        // inside the slice.
+        unsafe {
+            let needle = _mm_set1_epi8(byte as i8);
+
+            while offset + 16 <= s.len() {
+                let block = _mm_loadu_si128(s.as_ptr().add(offset) as *const __m128i);
+                let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(block, needle));
+
+                if mask != 0 {
+                    return Some(offset + mask.trailing_zeros() as usize);
+                }
+
+                offset += 16;
+            }
+        }
+
+        super::swar::find(&s[offset..], byte).map(|p| offset + p)
+    }
+}
+
+mod swar {
+    use std::convert::TryInto;
+
+    const LOW: u64 = 0x0101_0101_0101_0101;
+    const HIGH: u64 = 0x8080_8080_8080_8080;
+
+    // Non zero when a byte of the word is zero; the lowest flag is exact
+
+    fn zero(w: u64) -> u64 {
+        w.wrapping_sub(LOW) & !w & HIGH
+    }
+
+    pub fn find(s: &[u8], byte: u8) -> Option<usize> {
+        let needle = LOW * byte as u64;
+        let mut words = s.chunks_exact(8);
+        let mut offset = 0;
+
+        for word in words.by_ref() {
+            let flags = zero(u64::from_le_bytes(word.try_into().unwrap()) ^ needle);
+
+            if flags != 0 {
+                return Some(offset + flags.trailing_zeros() as usize / 8);
+            }
+
+            offset += 8;
+        }
+
+        words
+            .remainder()
+            .iter()
+            .position(|b| *b == byte)
+            .map(|p| offset + p)
+    }
+
+    #[cfg(test)]
+    mod tests_swar {
+        use crate::scan::swar::find;
+
+        #[test]
+        fn it_find_a_byte_in_every_position() {
+            for length in 0..20 {
+                for position in 0..length {
+                    let mut s = vec![b'a'; length];
+                    s[position] = b'"';
+
+                    assert_eq!(find(&s, b'"'), Some(position));
+                }
+            }
+        }
+
+        #[test]
+        fn it_find_the_first_byte_after_a_borrow() {
+            assert_eq!(find(b"\x01\x01\x00\x01\x01\x01\x01\x01", 1), Some(0));
+            assert_eq!(find(b"\x00\x01\x00\x01\x01\x01\x01\x01", 1), Some(1));
+        }
+
+        #[test]
+        fn it_cannot_find_a_byte() {
+            assert_eq!(find(&[0x80; 19], 0), None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_find {
+    use crate::scan::find;
+
+    #[test]
+    fn it_find_a_byte_in_every_position() {
+        for length in 0..40 {
+            for position in 0..length {
+                let mut s = vec![0xff; length];
+                s[position] = b'"';
+                s[length - 1] = b'"';
+
+                assert_eq!(find(&s, b'"'), Some(position));
+            }
+        }
+    }
+
+    #[test]
+    fn it_cannot_find_a_byte() {
+        assert_eq!(find(&[b'a'; 33], b'"'), None);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The TakeUntil and TakeWhile parsers
+//
+
+pub struct TakeUntil(pub u8);
+
+pub fn take_until(byte: u8) -> TakeUntil {
+    TakeUntil(byte)
+}
+
+impl Combine<&[u8]> for TakeUntil {}
+
+impl<'a> Parse<'a, &'a [u8]> for TakeUntil {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<&'a [u8]> {
+        let TakeUntil(byte) = self;
+        let rest = &s[o.min(s.len())..];
+
+        // The byte is not consumed; without it the input may still be continued
+        match find(rest, *byte) {
+            Some(n) => Success(&rest[..n], o + n),
+            None => report::track(s.len(), &[], stream::end_of_input(1)),
+        }
+    }
+}
+
+impl Check for TakeUntil {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let TakeUntil(byte) = self;
+
+        match find(&s[o.min(s.len())..], *byte) {
+            Some(n) => Success((), o + n),
+            None => report::track(s.len(), &[], stream::end_of_input(1)),
+        }
+    }
+}
+
+// Any byte but the searched one, and nothing when it comes first
+
+impl First for TakeUntil {
+    fn first(&self) -> FirstSet {
+        let TakeUntil(byte) = self;
+
+        let mut set = FirstSet::all();
+        set.bytes[*byte as usize] = false;
+        set
+    }
+}
+
+pub struct Class(pub [bool; 256]);
+
+impl Class {
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(u8) -> bool,
+    {
+        let mut table = [false; 256];
+
+        for (b, t) in table.iter_mut().enumerate() {
+            *t = f(b as u8);
+        }
+
+        Class(table)
+    }
+}
+
+pub struct TakeWhile(pub Class);
+
+pub fn take_while<F>(f: F) -> TakeWhile
+where
+    F: Fn(u8) -> bool,
+{
+    TakeWhile(Class::new(f))
+}
+
+// A class is an arbitrary set of bytes so the scan stays scalar, one lookup per byte
+
+impl TakeWhile {
+    fn scan(&self, s: &[u8], o: usize) -> usize {
+        let TakeWhile(Class(table)) = self;
+        let rest = &s[o..];
+
+        rest.iter()
+            .position(|b| !table[*b as usize])
+            .unwrap_or(rest.len())
+    }
+}
+
+impl Combine<&[u8]> for TakeWhile {}
+
+impl<'a> Parse<'a, &'a [u8]> for TakeWhile {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<&'a [u8]> {
+        let start = o.min(s.len());
+        let n = self.scan(s, start);
+
+        // The run may continue after the end of a partial input
+        if start + n == s.len() && stream::is_partial() {
+            return Incomplete(1);
+        }
+
+        Success(&s[start..start + n], start + n)
+    }
+}
+
+impl Check for TakeWhile {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        match self.parse(s, o) {
+            Success(_, e) => Success((), e),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl First for TakeWhile {
+    fn first(&self) -> FirstSet {
+        let TakeWhile(Class(table)) = self;
+
+        FirstSet {
+            bytes: *table,
+            nullable: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_scan {
+    use crate::scan::{take_until, take_while};
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_take_until_a_byte() {
+        let response = take_until(b'"').parse(b"abc\"d", 0);
+
+        assert_eq!(response.fold(|v, s| v == b"abc" && s == 3, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_take_until_a_missing_byte() {
+        let response = take_until(b'"').check(b"abcd", 1);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_take_while_a_class() {
+        let response = take_while(|b| b.is_ascii_digit()).parse(b"123a", 0);
+
+        assert_eq!(response.fold(|v, s| v == b"123" && s == 3, || false), true);
+    }
+
+    #[test]
+    fn it_take_while_up_to_the_end() {
+        let response = take_while(|b| b.is_ascii_digit()).parse(b"a123", 1);
+
+        assert_eq!(response.fold(|v, s| v == b"123" && s == 4, || false), true);
+    }
+
+    #[test]
+    fn it_take_nothing() {
+        let response = take_while(|b| b.is_ascii_digit()).check(b"a", 0);
+
+        assert_eq!(response.fold(|_, s| s == 0, || false), true);
+    }
+}
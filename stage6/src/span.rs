@@ -0,0 +1,181 @@
+//
+// Located results i.e. the byte range a value comes from
+//
+// This generalizes the `(&[u8], usize, usize)` value returned by `Delimited`: any parser can be
+// spanned, and `recognize` keeps the consumed slice only.
+//
+
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Spanned parser
+//
+
+pub struct Spanned<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn spanned<P, A>(p: P) -> Spanned<P, A>
+where
+    P: Combine<A>,
+{
+    Spanned(p, PhantomData)
+}
+
+impl<P, A> Combine<(A, Range<usize>)> for Spanned<P, A> where P: Combine<A> {}
+
+impl<'a, P, A> Parse<'a, (A, Range<usize>)> for Spanned<P, A>
+where
+    P: Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<(A, Range<usize>)> {
+        let Spanned(p, _) = self;
+
+        match p.parse(s, o) {
+            Success(a, e) => Success((a, o..e), e),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<P, A> Check for Spanned<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Spanned(p, _) = self;
+
+        p.check(s, o)
+    }
+}
+
+impl<P, A> First for Spanned<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Spanned(p, _) = self;
+
+        p.first()
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Recognize parser
+//
+
+pub struct Recognize<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn recognize<P, A>(p: P) -> Recognize<P, A>
+where
+    P: Combine<A>,
+{
+    Recognize(p, PhantomData)
+}
+
+impl<P, A> Combine<&[u8]> for Recognize<P, A> where P: Combine<A> {}
+
+// The value is never built: checking is enough to know what is consumed
+
+impl<'a, P, A> Parse<'a, &'a [u8]> for Recognize<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<&'a [u8]> {
+        let Recognize(p, _) = self;
+
+        match p.check(s, o) {
+            Success(_, e) => Success(&s[o..e], e),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<P, A> Check for Recognize<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Recognize(p, _) = self;
+
+        p.check(s, o)
+    }
+}
+
+impl<P, A> First for Recognize<P, A>
+where
+    P: First + Combine<A>,
+{
+    fn first(&self) -> FirstSet {
+        let Recognize(p, _) = self;
+
+        p.first()
+    }
+}
+
+#[cfg(test)]
+mod tests_span {
+    use crate::char;
+    use crate::delimited_string;
+    use crate::not;
+    use crate::span::{recognize, spanned};
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_spanned_character() {
+        let response = spanned(char('b')).parse(b"ab", 1);
+
+        assert_eq!(
+            response.fold(|v, e| v == ('b', 1..2) && e == 2, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_parse_a_spanned_repetition() {
+        let response = spanned(rep!(char('a'))).parse(b"aaab", 0);
+
+        assert_eq!(response.fold(|(_, r), _| r == (0..3), || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_spanned_character() {
+        let response = spanned(char('a')).parse(b"b", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_recognize_a_string() {
+        let response = recognize(and!(char('"'), optrep!(not('"')))).parse(b"\"aaa\"", 0);
+
+        assert_eq!(
+            response.fold(|v, e| v == b"\"aaa" && e == 4, || false),
+            true
+        );
+    }
+
+    #[test]
+    fn it_recognize_a_delimited_string() {
+        let response = recognize(delimited_string()).parse(b" \"aaa\" ", 1);
+
+        assert_eq!(response.fold(|v, _| v == b"\"aaa\"", || false), true);
+    }
+}
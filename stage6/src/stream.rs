@@ -0,0 +1,301 @@
+//
+// Streaming i.e. parsing an input received in chunks
+//
+// In partial mode primitives reaching the end of the input respond Incomplete instead of Reject
+// and combinators propagate it. The mode is a thread local flag so the Parse signature does not
+// change; it is only read when the end of the input is reached. Derived leaves follow it too and
+// memoized parsers never keep an Incomplete response.
+//
+
+use std::cell::Cell;
+use std::io;
+use std::io::Read;
+
+use response::Response::{Incomplete, Reject, Success};
+
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Partial inputs
+//
+
+thread_local! {
+    static PARTIAL: Cell<bool> = const { Cell::new(false) };
+}
+
+// Restores the previous mode when dropped, even if the parse panics
+
+struct Mode(bool);
+
+impl Drop for Mode {
+    fn drop(&mut self) {
+        PARTIAL.with(|p| p.set(self.0));
+    }
+}
+
+// Runs a parse where the input may be continued
+
+pub fn partial<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let _mode = Mode(PARTIAL.with(|p| p.replace(true)));
+
+    f()
+}
+
+pub fn is_partial() -> bool {
+    PARTIAL.with(|p| p.get())
+}
+
+// The response of a primitive missing `needed` bytes
+
+pub fn end_of_input<A>(needed: usize) -> Response<A> {
+    if is_partial() {
+        Incomplete(needed)
+    } else {
+        Reject
+    }
+}
+
+#[cfg(test)]
+mod tests_partial {
+    use response::Response::Incomplete;
+
+    use std::panic;
+
+    use crate::stream::{is_partial, partial};
+    use crate::{char, not, Parse};
+
+    #[test]
+    fn it_reject_the_end_of_a_complete_input() {
+        let response = char('a').parse(b"", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_ask_for_more_input() {
+        let response = partial(|| char('a').parse(b"", 0));
+
+        assert_eq!(matches!(response, Incomplete(1)), true);
+    }
+
+    #[test]
+    fn it_propagate_an_incomplete_repetition() {
+        let response = partial(|| and!(char('"'), optrep!(not('"'))).parse(b"\"aa", 0));
+
+        assert_eq!(matches!(response, Incomplete(1)), true);
+    }
+
+    #[test]
+    fn it_restore_the_mode_after_a_panic() {
+        let result = panic::catch_unwind(|| partial(|| panic!("parser failure")));
+
+        assert_eq!(result.is_err(), true);
+        assert_eq!(is_partial(), false);
+    }
+
+    #[test]
+    fn it_reject_a_partial_input_early() {
+        let response = partial(|| and!(char('a'), char('b')).parse(b"c", 0));
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Stream driver i.e. values parsed one after the other from any reader
+//
+
+// Input is read by chunks and parsing restarts from the beginning of the pending data each time
+// more input is required. Consumed bytes are dropped from the buffer. Each read asks for at least
+// the pending size so a long value is parsed again a logarithmic number of times only.
+
+pub struct Stream<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    chunk: usize,
+    eof: bool,
+}
+
+impl<R> Stream<R>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk(reader, 8 * 1024)
+    }
+
+    pub fn with_chunk(reader: R, chunk: usize) -> Self {
+        Stream {
+            reader,
+            buffer: Vec::new(),
+            chunk: chunk.max(1),
+            eof: false,
+        }
+    }
+
+    // The next value, None once the reader is exhausted
+
+    pub fn next<P, A>(&mut self, p: &P) -> io::Result<Option<A>>
+    where
+        P: for<'a> Parse<'a, A>,
+    {
+        loop {
+            let response = if self.eof {
+                p.parse(&self.buffer, 0)
+            } else {
+                partial(|| p.parse(&self.buffer, 0))
+            };
+
+            match response {
+                Success(v, o) if o > 0 => {
+                    self.buffer.drain(..o);
+                    return Ok(Some(v));
+                }
+                _ if self.eof && self.buffer.is_empty() => return Ok(None),
+                Success(_, _) if self.buffer.is_empty() => self.fill(self.chunk)?,
+                Success(_, _) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the parser does not consume the stream",
+                    ))
+                }
+                Incomplete(n) if !self.eof => {
+                    self.fill(n.max(self.chunk).max(self.buffer.len()))?
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unable to parse the stream",
+                    ))
+                }
+            }
+        }
+    }
+
+    fn fill(&mut self, size: usize) -> io::Result<()> {
+        let start = self.buffer.len();
+        self.buffer.resize(start + size, 0);
+
+        loop {
+            match self.reader.read(&mut self.buffer[start..]) {
+                Ok(read) => {
+                    self.buffer.truncate(start + read);
+                    self.eof = read == 0;
+                    return Ok(());
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.buffer.truncate(start);
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_stream {
+    use std::io;
+    use std::io::Read;
+
+    use crate::stream::Stream;
+    use crate::{char, map, not, Parse};
+
+    // A reader giving its data in two parts then by chunks of `size` bytes
+
+    struct Split<'a>(&'a [u8], usize, usize);
+
+    impl Read for Split<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Split(data, split, size) = self;
+            let length = if *split > 0 { *split } else { *size };
+            let length = length.min(data.len()).min(buf.len());
+
+            buf[..length].copy_from_slice(&data[..length]);
+            *data = &data[length..];
+            *split = 0;
+
+            Ok(length)
+        }
+    }
+
+    fn frame() -> impl for<'a> Parse<'a, String> {
+        map(
+            and!(char('<'), and!(optrep!(not('>')), char('>'))),
+            |(_, (v, _))| v.into_iter().collect(),
+        )
+    }
+
+    fn frames(reader: impl Read) -> io::Result<Vec<String>> {
+        let mut stream = Stream::with_chunk(reader, 3);
+        let mut values = Vec::new();
+
+        while let Some(v) = stream.next(&frame())? {
+            values.push(v);
+        }
+
+        Ok(values)
+    }
+
+    #[test]
+    fn it_parse_frames_split_at_every_boundary() {
+        let data = b"<hello><><world!>";
+
+        for split in 0..=data.len() {
+            for size in 1..4 {
+                let values = frames(Split(data, split, size)).unwrap();
+
+                assert_eq!(values, vec!["hello", "", "world!"]);
+            }
+        }
+    }
+
+    #[test]
+    fn it_fail_on_a_truncated_frame() {
+        let data = b"<hello><wor";
+
+        for split in 0..=data.len() {
+            assert_eq!(frames(Split(data, split, 1)).is_err(), true);
+        }
+    }
+
+    #[test]
+    fn it_fail_on_a_wrong_frame() {
+        assert_eq!(frames(&b"<a>b<c>"[..]).is_err(), true);
+    }
+
+    #[test]
+    fn it_fail_on_a_parser_consuming_nothing() {
+        let mut stream = Stream::new(&b"b"[..]);
+
+        assert_eq!(stream.next(&optrep!(char('a'))).is_err(), true);
+    }
+
+    // A reader counting its calls
+
+    struct Count<R>(R, usize);
+
+    impl<R: Read> Read for Count<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.1 += 1;
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn it_read_a_long_frame_in_few_parts() {
+        let data = format!("<{}>", "a".repeat(100_000));
+        let mut reader = Count(data.as_bytes(), 0);
+        let mut stream = Stream::with_chunk(&mut reader, 3);
+        let response = stream.next(&frame()).unwrap();
+
+        assert_eq!(response.map(|v| v.len()), Some(100_000));
+        assert_eq!(reader.1 < 64, true);
+    }
+}
@@ -0,0 +1,382 @@
+//
+// Escape aware delimited strings i.e. decoded only when an escape is present
+//
+// Backslash escapes are the JSON ones (`\n`, `\t`, `\uXXXX` with surrogate pairs, ...) plus the
+// escaped delimiter. Doubled delimiters are the CSV ones i.e. `""` inside a `"` string.
+//
+
+use std::borrow::Cow;
+use std::char;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::report;
+use crate::scan;
+use crate::stream;
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Escaped parser
+//
+
+pub struct Escaped {
+    pub delimiter: u8,
+    pub backslash: bool,
+    pub doubled: bool,
+}
+
+// A JSON like string
+
+pub fn escaped_string(delimiter: u8) -> Escaped {
+    Escaped {
+        delimiter,
+        backslash: true,
+        doubled: false,
+    }
+}
+
+// A CSV like string
+
+pub fn doubled_string(delimiter: u8) -> Escaped {
+    Escaped {
+        delimiter,
+        backslash: false,
+        doubled: true,
+    }
+}
+
+enum Escape {
+    Decoded(char, usize),
+    Missing(usize),
+    Invalid,
+}
+
+// The content of a string: raw bytes between escapes and decoded characters
+
+enum Piece<'a> {
+    Text(&'a [u8]),
+    Char(char),
+}
+
+impl Escaped {
+    pub fn backslash(self, backslash: bool) -> Self {
+        Escaped { backslash, ..self }
+    }
+
+    pub fn doubled(self, doubled: bool) -> Self {
+        Escaped { doubled, ..self }
+    }
+
+    fn expected(&self) -> Vec<String> {
+        vec![format!("'{}'", (self.delimiter as char).escape_default())]
+    }
+
+    // Scans the string at o and gives its pieces in order, checking allocates nothing
+
+    fn walk<'a, F>(&self, s: &'a [u8], o: usize, mut piece: F) -> Response<()>
+    where
+        F: FnMut(Piece<'a>),
+    {
+        match s.get(o) {
+            Some(c) if *c == self.delimiter => (),
+            Some(_) => return report::reject_with(o, || self.expected()),
+            None => return report::track_with(o, || self.expected(), stream::end_of_input(1)),
+        }
+
+        let mut start = o + 1;
+
+        loop {
+            let end = match scan::find(&s[start..], self.delimiter) {
+                Some(n) => start + n,
+                None if self.backslash && s[start..].contains(&b'\\') => s.len(),
+                None => {
+                    let response = stream::end_of_input(1);
+                    return report::track_with(s.len(), || self.expected(), response);
+                }
+            };
+
+            let escape = if self.backslash {
+                scan::find(&s[start..end], b'\\').map(|n| start + n)
+            } else {
+                None
+            };
+
+            if let Some(e) = escape {
+                piece(Piece::Text(&s[start..e]));
+
+                match self.escape(s, e) {
+                    Escape::Decoded(c, next) => {
+                        piece(Piece::Char(c));
+                        start = next;
+                    }
+                    Escape::Missing(n) => {
+                        return report::track(s.len(), &[], stream::end_of_input(n))
+                    }
+                    Escape::Invalid => return report::reject(e, &["escape sequence"]),
+                }
+
+                continue;
+            }
+
+            if self.doubled {
+                match s.get(end + 1) {
+                    Some(c) if *c == self.delimiter => {
+                        piece(Piece::Text(&s[start..=end]));
+                        start = end + 2;
+                        continue;
+                    }
+                    Some(_) => (),
+                    None if stream::is_partial() => return Incomplete(1),
+                    None => (),
+                }
+            }
+
+            piece(Piece::Text(&s[start..end]));
+
+            return Success((), end + 1);
+        }
+    }
+
+    // The escape following a backslash at o
+
+    fn escape(&self, s: &[u8], o: usize) -> Escape {
+        let c = match s.get(o + 1) {
+            Some(c) => *c,
+            None => return Escape::Missing(1),
+        };
+
+        let decoded = match c {
+            b'n' => '\n',
+            b't' => '\t',
+            b'r' => '\r',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'/' => '/',
+            b'\\' => '\\',
+            b'u' => return Self::unicode(s, o),
+            c if c == self.delimiter => c as char,
+            _ => return Escape::Invalid,
+        };
+
+        Escape::Decoded(decoded, o + 2)
+    }
+
+    fn unicode(s: &[u8], o: usize) -> Escape {
+        let high = match hexadecimal(s, o + 2) {
+            Ok(v) => v,
+            Err(missing) => return missing,
+        };
+
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).map_or(Escape::Invalid, |c| Escape::Decoded(c, o + 6));
+        }
+
+        // A high surrogate is followed by an escaped low surrogate
+        match s.get(o + 6..o + 8) {
+            Some(b"\\u") => (),
+            Some(_) => return Escape::Invalid,
+            None if s.len() < o + 8 && s[o + 6..].iter().zip(b"\\u").all(|(a, b)| a == b) => {
+                return Escape::Missing(o + 8 - s.len())
+            }
+            None => return Escape::Invalid,
+        }
+
+        match hexadecimal(s, o + 8) {
+            Ok(low) if (0xDC00..0xE000).contains(&low) => {
+                let c = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                char::from_u32(c).map_or(Escape::Invalid, |c| Escape::Decoded(c, o + 12))
+            }
+            Ok(_) => Escape::Invalid,
+            Err(missing) => missing,
+        }
+    }
+}
+
+fn hexadecimal(s: &[u8], o: usize) -> Result<u32, Escape> {
+    let digits = &s[o.min(s.len())..(o + 4).min(s.len())];
+
+    if !digits.iter().all(|d| d.is_ascii_hexdigit()) {
+        return Err(Escape::Invalid);
+    }
+
+    if digits.len() < 4 {
+        return Err(Escape::Missing(4 - digits.len()));
+    }
+
+    Ok(digits
+        .iter()
+        .fold(0, |v, d| v * 16 + (*d as char).to_digit(16).unwrap()))
+}
+
+impl<'a> Combine<Cow<'a, str>> for Escaped {}
+
+impl<'a> Parse<'a, Cow<'a, str>> for Escaped {
+    fn parse(&self, s: &'a [u8], o: usize) -> Response<Cow<'a, str>> {
+        // Decoded bytes are only allocated when a second piece is found
+        let mut borrowed: Option<&'a [u8]> = None;
+        let mut decoded: Option<Vec<u8>> = None;
+
+        let response = self.walk(s, o, |piece| match (piece, &mut decoded) {
+            (Piece::Text(text), None) if borrowed.is_none() => borrowed = Some(text),
+            (piece, buffer) => {
+                let buffer =
+                    buffer.get_or_insert_with(|| borrowed.take().unwrap_or_default().to_vec());
+
+                match piece {
+                    Piece::Text(text) => buffer.extend_from_slice(text),
+                    Piece::Char(c) => {
+                        buffer.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+                    }
+                }
+            }
+        });
+
+        match response {
+            Success(_, e) => {
+                let value = match decoded {
+                    None => std::str::from_utf8(borrowed.unwrap_or_default())
+                        .ok()
+                        .map(Cow::Borrowed),
+                    Some(buffer) => String::from_utf8(buffer).ok().map(Cow::Owned),
+                };
+
+                match value {
+                    Some(v) => Success(v, e),
+                    None => report::reject(o, &[]),
+                }
+            }
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+// Pieces are validated one by one: they are split on ASCII bytes so the string is valid UTF-8
+// when each piece is
+
+impl Check for Escaped {
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let mut valid = true;
+
+        let response = self.walk(s, o, |piece| {
+            if let Piece::Text(text) = piece {
+                valid = valid && std::str::from_utf8(text).is_ok();
+            }
+        });
+
+        match response {
+            Success(_, e) if valid => Success((), e),
+            Success(_, _) => report::reject(o, &[]),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl First for Escaped {
+    fn first(&self) -> FirstSet {
+        FirstSet::byte(self.delimiter)
+    }
+}
+
+#[cfg(test)]
+mod tests_escaped {
+    use std::borrow::Cow;
+
+    use crate::string::{doubled_string, escaped_string};
+    use crate::Check;
+    use crate::Parse;
+
+    fn decode(p: &impl for<'a> Parse<'a, Cow<'a, str>>, s: &[u8]) -> Option<(String, bool)> {
+        p.parse(s, 0).fold(
+            |v, e| {
+                assert_eq!(e, s.len());
+                Some((v.to_string(), matches!(v, Cow::Borrowed(_))))
+            },
+            || None,
+        )
+    }
+
+    #[test]
+    fn it_borrow_a_string_without_escape() {
+        let response = decode(&escaped_string(b'"'), b"\"hello\"");
+
+        assert_eq!(response, Some((String::from("hello"), true)));
+    }
+
+    #[test]
+    fn it_decode_backslash_escapes() {
+        let response = decode(&escaped_string(b'"'), br#""a\"b\\c\/\n\t""#);
+
+        assert_eq!(response, Some((String::from("a\"b\\c/\n\t"), false)));
+    }
+
+    #[test]
+    fn it_decode_unicode_escapes() {
+        let response = decode(&escaped_string(b'"'), br#""caf\u00e9 \ud83d\ude00""#);
+
+        assert_eq!(response, Some((String::from("café 😀"), false)));
+    }
+
+    #[test]
+    fn it_decode_an_escaped_delimiter() {
+        let response = decode(&escaped_string(b'\''), br#"'it\'s'"#);
+
+        assert_eq!(response, Some((String::from("it's"), false)));
+    }
+
+    #[test]
+    fn it_decode_doubled_delimiters() {
+        let response = decode(&doubled_string(b'"'), b"\"a\"\"b\"\"\"");
+
+        assert_eq!(response, Some((String::from("a\"b\""), false)));
+    }
+
+    #[test]
+    fn it_decode_both_escapes() {
+        let response = decode(&doubled_string(b'"').backslash(true), br#""a""\tb""#);
+
+        assert_eq!(response, Some((String::from("a\"\tb"), false)));
+    }
+
+    #[test]
+    fn it_stop_at_a_single_delimiter() {
+        let response = doubled_string(b'"').check(b"\"a\"b\"", 0);
+
+        assert_eq!(response.fold(|_, e| e == 3, || false), true);
+    }
+
+    #[test]
+    fn it_check_escapes_without_decoding() {
+        let response = escaped_string(b'"').check(br#""a\"b\u00e9""#, 0);
+
+        assert_eq!(response.fold(|_, e| e == 12, || false), true);
+    }
+
+    #[test]
+    fn it_cannot_check_an_invalid_utf8_string() {
+        let response = escaped_string(b'"').check(b"\"a\\n\xc3\"", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_an_invalid_escape() {
+        assert_eq!(decode(&escaped_string(b'"'), br#""a\qb""#), None);
+        assert_eq!(decode(&escaped_string(b'"'), br#""\ud83d""#), None);
+        assert_eq!(decode(&escaped_string(b'"'), br#""\u00g0""#), None);
+    }
+
+    #[test]
+    fn it_cannot_parse_an_unterminated_string() {
+        assert_eq!(decode(&escaped_string(b'"'), br#""abc\""#), None);
+        assert_eq!(decode(&escaped_string(b'"'), br#""abc\u00"#), None);
+    }
+}
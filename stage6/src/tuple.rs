@@ -0,0 +1,283 @@
+//
+// Variadic sequence and choice i.e. flat tuples instead of nested pairs
+//
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+use crate::choice::{First, FirstSet};
+use crate::Check;
+use crate::Combine;
+use crate::Parse;
+use crate::Response;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The seq! and alt! macros
+//
+
+#[macro_export]
+macro_rules! seq {
+    ( $ ( $ a: expr ),+ $ (,) ? ) => {
+        ( $ ( $ a, )+ )
+    };
+}
+
+#[macro_export]
+macro_rules! alt {
+    ( $ ( $ a: expr ),+ $ (,) ? ) => {
+        $crate::tuple::OneOf(( $ ( $ a, )+ ))
+    };
+}
+
+pub struct OneOf<T>(pub T);
+
+//  ------------------------------------------------------------------------------------------------
+//
+// Tuple implementations
+//
+
+macro_rules! tuple_impls {
+    ( $ ( ( $ ( $ p: ident $ a: ident $ v: ident $ i: tt ),+ ) )+ ) => {
+        $ (
+            // A tuple of parsers is a sequence
+
+            impl< $ ( $ p, $ a ),+ > Combine<( $ ( $ a, )+ )> for ( $ ( $ p, )+ )
+            where
+                $ ( $ p: Combine<$ a> ),+
+            {
+            }
+
+            impl<'a, $ ( $ p, $ a ),+ > Parse<'a, ( $ ( $ a, )+ )> for ( $ ( $ p, )+ )
+            where
+                $ ( $ p: Parse<'a, $ a> + Combine<$ a> ),+
+            {
+                fn parse(&self, s: &'a [u8], o: usize) -> Response<( $ ( $ a, )+ )> {
+                    let offset = o;
+
+                    $ (
+                        let ($ v, offset) = match self.$ i.parse(s, offset) {
+                            Success(v, s) => (v, s),
+                            Reject => return Reject,
+                            Incomplete(n) => return Incomplete(n),
+                            Fatal => return Fatal,
+                        };
+                    )+
+
+                    Success(( $ ( $ v, )+ ), offset)
+                }
+            }
+
+            impl< $ ( $ p ),+ > Check for ( $ ( $ p, )+ )
+            where
+                $ ( $ p: Check ),+
+            {
+                fn check(&self, s: &[u8], o: usize) -> Response<()> {
+                    let offset = o;
+
+                    $ (
+                        let offset = match self.$ i.check(s, offset) {
+                            Success(_, s) => s,
+                            Reject => return Reject,
+                            Incomplete(n) => return Incomplete(n),
+                            Fatal => return Fatal,
+                        };
+                    )+
+
+                    Success((), offset)
+                }
+            }
+
+            impl< $ ( $ p ),+ > First for ( $ ( $ p, )+ )
+            where
+                $ ( $ p: First ),+
+            {
+                fn first(&self) -> FirstSet {
+                    let mut set = FirstSet::empty();
+                    set.nullable = true;
+
+                    $ (
+                        if set.nullable {
+                            let first = self.$ i.first();
+                            let nullable = first.nullable;
+
+                            set = set.union(first);
+                            set.nullable = nullable;
+                        }
+                    )+
+
+                    set
+                }
+            }
+
+            // A tuple of parsers wrapped in OneOf is an ordered choice
+
+            impl<A, $ ( $ p ),+ > Combine<A> for OneOf<( $ ( $ p, )+ )>
+            where
+                $ ( $ p: Combine<A> ),+
+            {
+            }
+
+            impl<'a, A, $ ( $ p ),+ > Parse<'a, A> for OneOf<( $ ( $ p, )+ )>
+            where
+                $ ( $ p: Parse<'a, A> + Combine<A> ),+
+            {
+                fn parse(&self, s: &'a [u8], o: usize) -> Response<A> {
+                    $ (
+                        match (self.0).$ i.parse(s, o) {
+                            Success(v, s) => return Success(v, s),
+                            Incomplete(n) => return Incomplete(n),
+                            Fatal => return Fatal,
+                            Reject => (),
+                        }
+                    )+
+
+                    Reject
+                }
+            }
+
+            impl< $ ( $ p ),+ > Check for OneOf<( $ ( $ p, )+ )>
+            where
+                $ ( $ p: Check ),+
+            {
+                fn check(&self, s: &[u8], o: usize) -> Response<()> {
+                    $ (
+                        match (self.0).$ i.check(s, o) {
+                            Success(_, s) => return Success((), s),
+                            Incomplete(n) => return Incomplete(n),
+                            Fatal => return Fatal,
+                            Reject => (),
+                        }
+                    )+
+
+                    Reject
+                }
+            }
+
+            impl< $ ( $ p ),+ > First for OneOf<( $ ( $ p, )+ )>
+            where
+                $ ( $ p: First ),+
+            {
+                fn first(&self) -> FirstSet {
+                    FirstSet::empty() $ ( .union((self.0).$ i.first()) )+
+                }
+            }
+        )+
+    };
+}
+
+tuple_impls! {
+    (P1 A1 v1 0)
+    (P1 A1 v1 0, P2 A2 v2 1)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4, P6 A6 v6 5)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4, P6 A6 v6 5, P7 A7 v7 6)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4, P6 A6 v6 5, P7 A7 v7 6,
+     P8 A8 v8 7)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4, P6 A6 v6 5, P7 A7 v7 6,
+     P8 A8 v8 7, P9 A9 v9 8)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4, P6 A6 v6 5, P7 A7 v7 6,
+     P8 A8 v8 7, P9 A9 v9 8, P10 A10 v10 9)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4, P6 A6 v6 5, P7 A7 v7 6,
+     P8 A8 v8 7, P9 A9 v9 8, P10 A10 v10 9, P11 A11 v11 10)
+    (P1 A1 v1 0, P2 A2 v2 1, P3 A3 v3 2, P4 A4 v4 3, P5 A5 v5 4, P6 A6 v6 5, P7 A7 v7 6,
+     P8 A8 v8 7, P9 A9 v9 8, P10 A10 v10 9, P11 A11 v11 10, P12 A12 v12 11)
+}
+
+#[cfg(test)]
+mod tests_seq {
+    use crate::char;
+    use crate::choice::First;
+    use crate::not;
+    use crate::Check;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_flat_sequence() {
+        let response = seq!(char('a'), char('b'), char('c')).parse(b"abc", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('a', 'b', 'c'), || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_a_flat_sequence() {
+        let response = seq!(char('a'), char('b'), char('c')).parse(b"abd", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_parse_a_delimited_string() {
+        let parser = seq!(char('"'), optrep!(not('"')), char('"'));
+        let response = parser.parse(b"\"aaa\"", 0);
+
+        assert_eq!(response.fold(|(_, v, _), _| v.len() == 3, || false), true);
+    }
+
+    #[test]
+    fn it_check_a_twelve_elements_sequence() {
+        let parser = seq!(
+            char('a'),
+            char('b'),
+            char('c'),
+            char('d'),
+            char('e'),
+            char('f'),
+            char('g'),
+            char('h'),
+            char('i'),
+            char('j'),
+            char('k'),
+            char('l'),
+        );
+        let response = parser.check(b"abcdefghijkl", 0);
+
+        assert_eq!(response.fold(|_, s| s == 12, || false), true);
+    }
+
+    #[test]
+    fn it_compute_a_sequence_first_set() {
+        let set = seq!(optrep!(char('a')), char('b'), char('c')).first();
+
+        assert_eq!(
+            set.accept(b'a') && set.accept(b'b') && !set.accept(b'c'),
+            true
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_alt {
+    use crate::char;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_the_first_alternative() {
+        let response = alt!(char('a'), char('b'), char('c')).parse(b"a", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'a', || false), true);
+    }
+
+    #[test]
+    fn it_parse_the_last_alternative() {
+        let response = alt!(char('a'), char('b'), char('c')).parse(b"c", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'c', || false), true);
+    }
+
+    #[test]
+    fn it_cannot_parse_any_alternative() {
+        let response = alt!(char('a'), char('b'), char('c')).parse(b"d", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_parse_alternatives_of_flat_sequences() {
+        let parser = alt!(seq!(char('a'), char('b')), seq!(char('a'), char('c')));
+        let response = parser.parse(b"ac", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('a', 'c'), || false), true);
+    }
+}
@@ -0,0 +1,756 @@
+#![allow(dead_code)]
+
+//
+//   Stage 7: Parsing with a context i.e. user defined state
+//
+// Parse receives a `&mut Ctx<S>` with the user state: indentation levels, symbol tables,
+// recursion depth... A rejected parser leaves the context unchanged so a choice can try its
+// next alternative from the same state. Check stays context-free and is stage6's: a guard
+// depends on the state so it has no Check, and a grammar with guards is checked with
+// `Ctx::check` which runs Parse and discards the updates.
+//
+// A checkpoint is a mark in an undo log: the state is only saved when it is changed after the
+// latest checkpoint, instead of being cloned at each step of a sequence.
+//
+
+use std::marker::PhantomData;
+
+use response::Response::{Fatal, Incomplete, Reject, Success};
+
+pub use stage6::Check;
+pub use stage6::Combine;
+
+//  ------------------------------------------------------------------------------------------------
+
+type Response<A> = response::Response<A, usize>;
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The parse context
+//
+
+pub struct Ctx<S> {
+    state: S,
+    saved: Vec<S>,
+    marks: Vec<usize>,
+}
+
+// Checkpoints are released in reverse order of creation
+
+pub struct Checkpoint(usize);
+
+impl<S> Ctx<S> {
+    pub fn new(state: S) -> Self {
+        Ctx {
+            state,
+            saved: Vec::new(),
+            marks: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn into_state(self) -> S {
+        self.state
+    }
+
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let mark = self.saved.len();
+
+        self.marks.push(mark);
+        Checkpoint(mark)
+    }
+
+    // The state saved at the mark, if any, is the state of the checkpoint
+
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        let Checkpoint(mark) = checkpoint;
+
+        self.marks.pop();
+
+        if self.saved.len() > mark {
+            self.saved.truncate(mark + 1);
+            self.state = self.saved.pop().unwrap();
+        }
+    }
+
+    // Only the state of the enclosing checkpoint is kept
+
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        let Checkpoint(_) = checkpoint;
+
+        self.marks.pop();
+
+        match self.marks.last() {
+            Some(mark) => self.saved.truncate(mark + 1),
+            None => self.saved.clear(),
+        }
+    }
+
+    // Runs `f` from a checkpoint which is rolled back when it rejects
+
+    pub fn attempt<A, F>(&mut self, f: F) -> Response<A>
+    where
+        F: FnOnce(&mut Self) -> Response<A>,
+    {
+        let checkpoint = self.checkpoint();
+        let response = f(self);
+
+        match response {
+            Reject => self.rollback(checkpoint),
+            _ => self.commit(checkpoint),
+        }
+
+        response
+    }
+
+    // Runs `p` from a checkpoint which is always rolled back
+
+    pub fn check<'a, P, A>(&mut self, p: &P, s: &'a [u8], o: usize) -> Response<()>
+    where
+        P: Parse<'a, A, S>,
+    {
+        let checkpoint = self.checkpoint();
+        let response = p.parse(self, s, o);
+
+        self.rollback(checkpoint);
+
+        match response {
+            Success(_, s) => Success((), s),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<S> Ctx<S>
+where
+    S: Clone,
+{
+    pub fn state_mut(&mut self) -> &mut S {
+        if self.marks.last() == Some(&self.saved.len()) {
+            self.saved.push(self.state.clone());
+        }
+
+        &mut self.state
+    }
+}
+
+pub trait Parse<'a, A, S> {
+    fn parse(&self, c: &mut Ctx<S>, s: &'a [u8], o: usize) -> Response<A>;
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Lift parser i.e. a stage6 parser ignoring the context
+//
+
+pub struct Lift<P, A>(pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn lift<P, A>(p: P) -> Lift<P, A>
+where
+    P: Combine<A>,
+{
+    Lift(p, PhantomData)
+}
+
+pub fn char(c: char) -> Lift<stage6::Satisfy<impl Fn(char) -> bool>, char> {
+    lift(stage6::char(c))
+}
+
+impl<P, A> Combine<A> for Lift<P, A> where P: Combine<A> {}
+
+impl<'a, P, A, S> Parse<'a, A, S> for Lift<P, A>
+where
+    P: stage6::Parse<'a, A> + Combine<A>,
+{
+    fn parse(&self, _: &mut Ctx<S>, s: &'a [u8], o: usize) -> Response<A> {
+        let Lift(p, _) = self;
+
+        p.parse(s, o)
+    }
+}
+
+impl<P, A> Check for Lift<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Lift(p, _) = self;
+
+        p.check(s, o)
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The And parser
+//
+
+pub struct And<L, R, A, B>(pub L, pub R, pub PhantomData<A>, pub PhantomData<B>)
+where
+    L: Combine<A>,
+    R: Combine<B>;
+
+#[macro_export]
+macro_rules! and {
+    ( $ a: expr, $ b: expr) => {
+        $crate::And(
+            $a,
+            $b,
+            ::std::marker::PhantomData,
+            ::std::marker::PhantomData,
+        )
+    };
+}
+
+impl<L, R, A, B> Combine<(A, B)> for And<L, R, A, B>
+where
+    L: Combine<A>,
+    R: Combine<B>,
+{
+}
+
+impl<'a, L, R, A, B, S> Parse<'a, (A, B), S> for And<L, R, A, B>
+where
+    L: Parse<'a, A, S> + Combine<A>,
+    R: Parse<'a, B, S> + Combine<B>,
+{
+    fn parse(&self, c: &mut Ctx<S>, s: &'a [u8], o: usize) -> Response<(A, B)> {
+        let And(left, right, _, _) = self;
+
+        // When the right parser rejects the updates of the left one are discarded
+        c.attempt(|c| match left.parse(c, s, o) {
+            Success(v1, s1) => match right.parse(c, s, s1) {
+                Success(v2, s2) => Success((v1, v2), s2),
+                Reject => Reject,
+                Incomplete(n) => Incomplete(n),
+                Fatal => Fatal,
+            },
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        })
+    }
+}
+
+impl<L, R, A, B> Check for And<L, R, A, B>
+where
+    L: Check + Combine<A>,
+    R: Check + Combine<B>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let And(left, right, _, _) = self;
+
+        match left.check(s, o) {
+            Success(_, s1) => right.check(s, s1),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Or parser
+//
+
+pub struct Or<L, R, A>(pub L, pub R, pub PhantomData<A>)
+where
+    L: Combine<A>,
+    R: Combine<A>;
+
+#[macro_export]
+macro_rules! or {
+    ( $ a: expr, $ b: expr) => {
+        $crate::Or($a, $b, ::std::marker::PhantomData)
+    };
+}
+
+impl<L, R, A> Combine<A> for Or<L, R, A>
+where
+    L: Combine<A>,
+    R: Combine<A>,
+{
+}
+
+impl<'a, L, R, A, S> Parse<'a, A, S> for Or<L, R, A>
+where
+    L: Parse<'a, A, S> + Combine<A>,
+    R: Parse<'a, A, S> + Combine<A>,
+{
+    fn parse(&self, c: &mut Ctx<S>, s: &'a [u8], o: usize) -> Response<A> {
+        let Or(left, right, _) = self;
+
+        match left.parse(c, s, o) {
+            Success(v, s) => Success(v, s),
+            Reject => right.parse(c, s, o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<L, R, A> Check for Or<L, R, A>
+where
+    L: Check + Combine<A>,
+    R: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Or(left, right, _) = self;
+
+        match left.check(s, o) {
+            Success(_, s) => Success((), s),
+            Reject => right.check(s, o),
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Repeatable parser
+//
+
+pub struct Repeat<P, A>(pub bool, pub P, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+#[macro_export]
+macro_rules! rep {
+    ( $ a: expr) => {
+        $crate::Repeat(false, $a, ::std::marker::PhantomData)
+    };
+}
+
+#[macro_export]
+macro_rules! optrep {
+    ( $ a: expr) => {
+        $crate::Repeat(true, $a, ::std::marker::PhantomData)
+    };
+}
+
+impl<P, A> Combine<Vec<A>> for Repeat<P, A> where P: Combine<A> {}
+
+impl<'a, P, A, S> Parse<'a, Vec<A>, S> for Repeat<P, A>
+where
+    P: Parse<'a, A, S> + Combine<A>,
+{
+    fn parse(&self, c: &mut Ctx<S>, s: &'a [u8], o: usize) -> Response<Vec<A>> {
+        let Repeat(opt, p, _) = self;
+
+        let mut values = Vec::with_capacity(if *opt { 0 } else { 1 });
+        let mut offset = o;
+
+        loop {
+            match p.parse(c, s, offset) {
+                Success(a, s) => {
+                    offset = s;
+                    values.push(a);
+                }
+                Reject => {
+                    if !*opt && values.is_empty() {
+                        return Reject;
+                    }
+
+                    return Success(values, offset);
+                }
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+    }
+}
+
+impl<P, A> Check for Repeat<P, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Repeat(opt, p, _) = self;
+
+        let mut offset = o;
+
+        loop {
+            match p.check(s, offset) {
+                Success(_, s) => offset = s,
+                Reject => {
+                    if !*opt && offset == o {
+                        return Reject;
+                    }
+
+                    return Success((), offset);
+                }
+                Incomplete(n) => return Incomplete(n),
+                Fatal => return Fatal,
+            }
+        }
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Map parser
+//
+
+pub struct Map<P, F, A>(pub P, pub F, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn map<P, F, A, B>(p: P, f: F) -> Map<P, F, A>
+where
+    P: Combine<A>,
+    F: Fn(A) -> B,
+{
+    Map(p, f, PhantomData)
+}
+
+impl<P, F, A, B> Combine<B> for Map<P, F, A>
+where
+    P: Combine<A>,
+    F: Fn(A) -> B,
+{
+}
+
+impl<'a, P, F, A, B, S> Parse<'a, B, S> for Map<P, F, A>
+where
+    P: Parse<'a, A, S> + Combine<A>,
+    F: Fn(A) -> B,
+{
+    fn parse(&self, c: &mut Ctx<S>, s: &'a [u8], o: usize) -> Response<B> {
+        let Map(p, f, _) = self;
+
+        match p.parse(c, s, o) {
+            Success(a, s) => Success(f(a), s),
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+impl<P, F, A> Check for Map<P, F, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Map(p, _, _) = self;
+
+        p.check(s, o)
+    }
+}
+
+#[cfg(test)]
+mod tests_combinators {
+    use response::Response::Fatal;
+
+    use crate::char;
+    use crate::lift;
+    use crate::map;
+    use crate::Check;
+    use crate::Ctx;
+    use crate::Parse;
+
+    #[test]
+    fn it_parse_a_sequence() {
+        let response = and!(char('a'), char('b')).parse(&mut Ctx::new(()), b"ab", 0);
+
+        assert_eq!(response.fold(|v, _| v == ('a', 'b'), || false), true);
+    }
+
+    #[test]
+    fn it_parse_an_alternative() {
+        let response = or!(char('a'), char('b')).parse(&mut Ctx::new(()), b"b", 0);
+
+        assert_eq!(response.fold(|v, _| v == 'b', || false), true);
+    }
+
+    #[test]
+    fn it_parse_and_map_a_repetition() {
+        let parser = map(rep!(char('a')), |v| v.len());
+        let response = parser.parse(&mut Ctx::new(()), b"aaab", 0);
+
+        assert_eq!(response.fold(|v, s| v == 3 && s == 3, || false), true);
+    }
+
+    #[test]
+    fn it_stop_alternatives_after_a_cut() {
+        let committed = lift(stage6::and!(
+            stage6::char('{'),
+            stage6::cut(stage6::char('}'))
+        ));
+        let parser = or!(map(committed, |_| '{'), char('{'));
+        let response = parser.parse(&mut Ctx::new(()), b"{x", 0);
+
+        assert_eq!(matches!(response, Fatal), true);
+    }
+
+    #[test]
+    fn it_check_without_context() {
+        let response = and!(char('a'), optrep!(char('b'))).check(b"abb", 0);
+
+        assert_eq!(response.fold(|_, s| s == 3, || false), true);
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Read parser i.e. a value computed from the state
+//
+
+pub struct Read<F, S>(pub F, pub PhantomData<S>);
+
+pub fn read<F, S, A>(f: F) -> Read<F, S>
+where
+    F: Fn(&S) -> A,
+{
+    Read(f, PhantomData)
+}
+
+impl<F, S, A> Combine<A> for Read<F, S> where F: Fn(&S) -> A {}
+
+impl<'a, F, S, A> Parse<'a, A, S> for Read<F, S>
+where
+    F: Fn(&S) -> A,
+{
+    fn parse(&self, c: &mut Ctx<S>, _: &'a [u8], o: usize) -> Response<A> {
+        let Read(f, _) = self;
+
+        Success(f(c.state()), o)
+    }
+}
+
+impl<F, S> Check for Read<F, S> {
+    fn check(&self, _: &[u8], o: usize) -> Response<()> {
+        Success((), o)
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Update parser i.e. the state changed by a parsed value
+//
+
+pub struct Update<P, F, A>(pub P, pub F, pub PhantomData<A>)
+where
+    P: Combine<A>;
+
+pub fn update<P, F, A, S>(p: P, f: F) -> Update<P, F, A>
+where
+    P: Combine<A>,
+    F: Fn(&mut S, &A),
+{
+    Update(p, f, PhantomData)
+}
+
+impl<P, F, A> Combine<A> for Update<P, F, A> where P: Combine<A> {}
+
+impl<'a, P, F, A, S> Parse<'a, A, S> for Update<P, F, A>
+where
+    P: Parse<'a, A, S> + Combine<A>,
+    F: Fn(&mut S, &A),
+    S: Clone,
+{
+    fn parse(&self, c: &mut Ctx<S>, s: &'a [u8], o: usize) -> Response<A> {
+        let Update(p, f, _) = self;
+
+        match p.parse(c, s, o) {
+            Success(a, s) => {
+                f(c.state_mut(), &a);
+                Success(a, s)
+            }
+            Reject => Reject,
+            Incomplete(n) => Incomplete(n),
+            Fatal => Fatal,
+        }
+    }
+}
+
+// The update does not change the accepted inputs
+
+impl<P, F, A> Check for Update<P, F, A>
+where
+    P: Check + Combine<A>,
+{
+    fn check(&self, s: &[u8], o: usize) -> Response<()> {
+        let Update(p, _, _) = self;
+
+        p.check(s, o)
+    }
+}
+
+//  ------------------------------------------------------------------------------------------------
+//
+// The Guard parser i.e. a condition on the state
+//
+
+pub struct Guard<F, S>(pub F, pub PhantomData<S>);
+
+pub fn guard<F, S>(f: F) -> Guard<F, S>
+where
+    F: Fn(&S) -> bool,
+{
+    Guard(f, PhantomData)
+}
+
+impl<F, S> Combine<()> for Guard<F, S> where F: Fn(&S) -> bool {}
+
+impl<'a, F, S> Parse<'a, (), S> for Guard<F, S>
+where
+    F: Fn(&S) -> bool,
+{
+    fn parse(&self, c: &mut Ctx<S>, _: &'a [u8], o: usize) -> Response<()> {
+        let Guard(f, _) = self;
+
+        if f(c.state()) {
+            Success((), o)
+        } else {
+            Reject
+        }
+    }
+}
+
+// A guard has no Check: its outcome depends on the state, see `Ctx::check`
+
+#[cfg(test)]
+mod tests_state {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use stage6::Satisfy;
+
+    use crate::char;
+    use crate::guard;
+    use crate::lift;
+    use crate::map;
+    use crate::read;
+    use crate::update;
+    use crate::Check;
+    use crate::Ctx;
+    use crate::Parse;
+
+    // Variables are declared with `+x` and used with `x`
+
+    fn statements() -> impl for<'a> Parse<'a, Vec<char>, Vec<char>> + Check {
+        let name = || lift(Satisfy(|c: char| c.is_ascii_lowercase()));
+        let declare = map(
+            and!(
+                char('+'),
+                update(name(), |names: &mut Vec<char>, n| names.push(*n))
+            ),
+            |(_, n)| n,
+        );
+        let known = map(
+            and!(name(), read(|names: &Vec<char>| names.clone())),
+            |(n, names)| if names.contains(&n) { n } else { '?' },
+        );
+
+        rep!(or!(declare, known))
+    }
+
+    #[test]
+    fn it_update_the_state() {
+        let mut context = Ctx::new(vec![]);
+        let response = statements().parse(&mut context, b"+a+ba", 0);
+
+        assert_eq!(
+            response.fold(|v, _| v == vec!['a', 'b', 'a'], || false),
+            true
+        );
+        assert_eq!(context.into_state(), vec!['a', 'b']);
+    }
+
+    #[test]
+    fn it_read_the_state() {
+        let mut context = Ctx::new(vec![]);
+        let response = statements().parse(&mut context, b"a+a", 0);
+
+        assert_eq!(response.fold(|v, _| v == vec!['?', 'a'], || false), true);
+    }
+
+    #[test]
+    fn it_guard_on_the_state() {
+        let depth = guard(|depth: &usize| *depth < 2);
+        let open = update(char('('), |depth: &mut usize, _| *depth += 1);
+        let parser = rep!(and!(depth, open));
+        let mut context = Ctx::new(0);
+        let response = parser.parse(&mut context, b"(((", 0);
+
+        assert_eq!(response.fold(|_, s| s == 2, || false), true);
+        assert_eq!(context.into_state(), 2);
+    }
+
+    #[test]
+    fn it_rollback_on_backtracking() {
+        let declare = update(char('a'), |n: &mut usize, _| *n += 1);
+        let parser = or!(map(and!(declare, char('!')), |(c, _)| c), char('a'));
+        let mut context = Ctx::new(0);
+        let response = parser.parse(&mut context, b"a?", 0);
+
+        assert_eq!(response.fold(|v, s| v == 'a' && s == 1, || false), true);
+        assert_eq!(context.into_state(), 0);
+    }
+
+    #[test]
+    fn it_rollback_nested_checkpoints() {
+        let count = || update(char('a'), |n: &mut usize, _| *n += 1);
+        let parser = or!(
+            map(and!(count(), and!(count(), char('!'))), |(c, _)| c),
+            map(and!(count(), char('a')), |(c, _)| c)
+        );
+        let mut context = Ctx::new(0);
+        let response = parser.parse(&mut context, b"aa?", 0);
+
+        assert_eq!(response.fold(|_, s| s == 2, || false), true);
+        assert_eq!(context.into_state(), 1);
+    }
+
+    #[test]
+    fn it_check_with_guards() {
+        let parser = and!(guard(|_: &usize| false), char('a'));
+        let response = Ctx::new(0).check(&parser, b"a", 0);
+
+        assert_eq!(response.fold(|_, _| false, || true), true);
+    }
+
+    #[test]
+    fn it_discard_updates_when_checking() {
+        let depth = guard(|depth: &usize| *depth < 2);
+        let open = update(char('('), |depth: &mut usize, _| *depth += 1);
+        let parser = rep!(and!(depth, open));
+        let mut context = Ctx::new(0);
+        let response = context.check(&parser, b"(((", 0);
+
+        assert_eq!(response.fold(|_, s| s == 2, || false), true);
+        assert_eq!(context.into_state(), 0);
+    }
+
+    // The state is only saved when it is changed after a checkpoint
+
+    struct Clones(Rc<Cell<usize>>);
+
+    impl Clone for Clones {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            Clones(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn it_do_not_save_an_unchanged_state() {
+        let clones = Rc::new(Cell::new(0));
+        let parser = rep!(and!(char('a'), char('b')));
+        let mut context = Ctx::new(Clones(clones.clone()));
+        let response = parser.parse(&mut context, b"ababab", 0);
+
+        assert_eq!(response.fold(|v, _| v.len() == 3, || false), true);
+        assert_eq!(clones.get(), 0);
+    }
+}